@@ -0,0 +1,94 @@
+use aoc_2019::intcode_computer::{read_program, Engine, Event, IntcodeComputer, NoIO, SingleIO, IO};
+use itertools::Itertools;
+use std::{
+    fs::File,
+    time::{Duration, Instant},
+};
+
+type Workload = fn(&[i64], Engine) -> i64;
+
+fn load(day: u32) -> Vec<i64> {
+    read_program(File::open(format!("input/day{:02}", day)).expect("unable to open input file"))
+}
+
+fn day02(program: &[i64], engine: Engine) -> i64 {
+    let template = IntcodeComputer::new(program, NoIO).with_engine(engine);
+
+    for noun in 0..100 {
+        for verb in 0..100 {
+            let mut cpu = template.clone();
+            cpu.write(1, noun).unwrap();
+            cpu.write(2, verb).unwrap();
+            cpu.run().unwrap();
+
+            if cpu.read(0) == 19690720 {
+                return 100 * noun + verb;
+            }
+        }
+    }
+    unreachable!()
+}
+
+fn day07(program: &[i64], engine: Engine) -> i64 {
+    let template = IntcodeComputer::new(program, NoIO).with_engine(engine);
+
+    (5..10).permutations(5)
+        .map(|phases| {
+            let mut cpus = phases.iter()
+                .map(|&phase| {
+                    let mut cpu = template.clone();
+                    assert_eq!(cpu.run_until_event().unwrap(), Event::NeedInput);
+                    cpu.provide_input(phase);
+                    cpu
+                })
+                .collect::<Vec<_>>();
+
+            let mut signal = 0;
+            loop {
+                for cpu in cpus.iter_mut() {
+                    cpu.provide_input(signal);
+                    match cpu.run_until_event().unwrap() {
+                        Event::Output(val) => signal = val,
+                        _ => return signal,
+                    }
+                }
+            }
+        })
+        .max()
+        .unwrap()
+}
+
+fn day09(program: &[i64], engine: Engine) -> i64 {
+    let mut cpu = IntcodeComputer::new(program, SingleIO::new(2)).with_engine(engine);
+    cpu.run().unwrap();
+    cpu.io.read().unwrap()
+}
+
+fn time<F: FnMut() -> i64>(runs: u32, mut f: F) -> (i64, Duration) {
+    let result = f();
+    let start = Instant::now();
+    for _ in 0..runs {
+        assert_eq!(f(), result);
+    }
+    (result, start.elapsed() / runs)
+}
+
+fn main() {
+    let benches: Vec<(&str, u32, Workload, Vec<i64>)> = vec![
+        ("day02 noun/verb search", 3, day02, load(2)),
+        ("day07 feedback loops", 100, day07, load(7)),
+        ("day09 boost", 3, day09, load(9)),
+    ];
+
+    println!("{:<24}{:>14}{:>14}{:>10}", "", "interpreter", "cached", "speedup");
+    for (name, runs, f, program) in benches {
+        let (r1, t1) = time(runs, || f(&program, Engine::Interpreter));
+        let (r2, t2) = time(runs, || f(&program, Engine::Cached));
+        assert_eq!(r1, r2, "{}: engines disagree", name);
+
+        println!(
+            "{:<24}{:>12.2?}{:>14.2?}{:>9.2}x",
+            name, t1, t2, t1.as_secs_f64() / t2.as_secs_f64()
+        );
+    }
+}
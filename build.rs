@@ -25,7 +25,7 @@ fn gen_solutions_mod<P: AsRef<Path>>(p: P, days: &Vec<u32>) -> io::Result<()> {
         "// GENERATED ON BUILD, DO NOT EDIT"
     )?;
 
-    writeln!(f, "use crate::solver::Solver;")?;
+    writeln!(f, "use crate::solver::{{DayResult, Options, Solver, SolverError}};")?;
 
     writeln!(f)?;
     for day in days {
@@ -34,17 +34,22 @@ fn gen_solutions_mod<P: AsRef<Path>>(p: P, days: &Vec<u32>) -> io::Result<()> {
 
     writeln!(f)?;
 
+    let list = days.iter().map(u32::to_string).collect::<Vec<_>>();
+    writeln!(f, "pub const DAYS: &[i32] = &[{}];", list.join(", "))?;
+
+    writeln!(f)?;
+
     writeln!(
         f,
-        "pub fn exec_day(day: i32) {{
+        "pub fn exec_day(day: i32, options: &Options) -> DayResult {{
     match day {{"
     )?;
     for day in days {
-        writeln!(f, "        {0} => day{0:02}::Problem {{}}.solve(day),", day)?;
+        writeln!(f, "        {0} => day{0:02}::Problem {{}}.solve(day, options),", day)?;
     }
     writeln!(
         f,
-        "        d => println!(\"Day {{}} hasn't been solved yet :(\", d),
+        "        d => DayResult::failed(d, SolverError::new(\"hasn't been solved yet :(\")),
     }}
 }}"
     )?;
@@ -62,7 +67,7 @@ fn gen_solutions(dir: &str, days: &[u32]) -> io::Result<()> {
         let mut f = File::create(file)?;
         writeln!(
             f,
-            "use crate::solver::Solver;
+            "use crate::solver::{{Solver, SolverError}};
 use std::io::Read;
 
 pub struct Problem;
@@ -72,14 +77,16 @@ impl Solver for Problem {{
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {{}}
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {{
+        Ok(())
+    }}
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {{
-        0
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {{
+        Ok(0)
     }}
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {{
-        0
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {{
+        Ok(0)
     }}
 }}"
         )?;
@@ -97,7 +104,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     gen_solutions_mod(&solutions_mod_output_path, &days)?;
 
-    gen_solutions(&output_dir, &days)?;
+    gen_solutions(output_dir, &days)?;
 
     Ok(())
 }
@@ -0,0 +1,196 @@
+use aoc_2019::intcode_computer::{
+    debugger::{Debugger, Stop, WatchKind},
+    decode_at, read_program, IntcodeComputer, Line, NoIO,
+};
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, Write},
+    process,
+};
+
+const HELP: &str = "\
+commands:
+  s, step [n]         execute n instructions (default 1)
+  c, continue         run until a breakpoint, watchpoint, input request or halt
+  b, break <addr>     set a breakpoint
+  d, delete <addr>    delete a breakpoint
+  watch <addr>        stop after writes to addr
+  rwatch <addr>       stop after reads of addr
+  awatch <addr>       stop after reads or writes of addr
+  unwatch <addr>      delete a watchpoint
+  info                list breakpoints and watchpoints
+  i, input <n>...     queue input values
+  r, regs             show pc and relative base
+  x <addr> [len]      dump memory (default 16 words)
+  l, list [addr] [n]  disassemble n instructions (default 8 from pc)
+  h, help             show this help
+  q, quit             exit";
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: intcode-debugger <program>");
+            process::exit(2);
+        }
+    };
+
+    let program = match File::open(&path) {
+        Ok(f) => read_program(f),
+        Err(e) => {
+            eprintln!("unable to open {}: {}", path, e);
+            process::exit(1);
+        }
+    };
+
+    let mut dbg = Debugger::new(IntcodeComputer::new(&program, NoIO));
+    let stdin = io::stdin();
+
+    prompt();
+    for line in stdin.lock().lines().map_while(Result::ok) {
+        let args = line.split_whitespace().collect::<Vec<_>>();
+
+        if let Some(&cmd) = args.first() {
+            if cmd == "q" || cmd == "quit" {
+                break;
+            }
+
+            if let Err(msg) = exec(&mut dbg, cmd, &args[1..]) {
+                println!("error: {}", msg);
+            }
+        }
+
+        prompt();
+    }
+}
+
+fn prompt() {
+    print!("(icdb) ");
+    let _ = io::stdout().flush();
+}
+
+fn exec(dbg: &mut Debugger<NoIO>, cmd: &str, args: &[&str]) -> Result<(), String> {
+    let arg = |i: usize| -> Result<Option<i64>, String> {
+        args.get(i)
+            .map(|a| a.parse().map_err(|_| format!("invalid number {:?}", a)))
+            .transpose()
+    };
+    let addr = |i: usize| -> Result<usize, String> {
+        match arg(i)? {
+            Some(n) if n >= 0 => Ok(n as usize),
+            Some(n) => Err(format!("invalid address {}", n)),
+            None => Err("missing address".into()),
+        }
+    };
+
+    match cmd {
+        "s" | "step" => {
+            for _ in 0..arg(0)?.unwrap_or(1) {
+                let stop = dbg.step().map_err(|e| e.to_string())?;
+                if stop != Stop::Step {
+                    report(dbg, stop);
+                    return Ok(());
+                }
+            }
+            report(dbg, Stop::Step);
+        }
+        "c" | "continue" => {
+            let stop = dbg.resume().map_err(|e| e.to_string())?;
+            report(dbg, stop);
+        }
+        "b" | "break" => dbg.add_breakpoint(addr(0)?),
+        "d" | "delete" => {
+            if !dbg.remove_breakpoint(addr(0)?) {
+                return Err("no such breakpoint".into());
+            }
+        }
+        "watch" => dbg.add_watchpoint(addr(0)?, WatchKind::Write),
+        "rwatch" => dbg.add_watchpoint(addr(0)?, WatchKind::Read),
+        "awatch" => dbg.add_watchpoint(addr(0)?, WatchKind::Access),
+        "unwatch" => {
+            if !dbg.remove_watchpoint(addr(0)?) {
+                return Err("no such watchpoint".into());
+            }
+        }
+        "info" => {
+            for addr in dbg.breakpoints() {
+                println!("breakpoint {:04}", addr);
+            }
+            for (addr, kind) in dbg.watchpoints() {
+                println!("watchpoint {:04} ({:?})", addr, kind);
+            }
+        }
+        "i" | "input" => {
+            for i in 0..args.len() {
+                dbg.input.push_back(arg(i)?.unwrap());
+            }
+        }
+        "r" | "regs" => {
+            let (pc, rel_base) = dbg.registers();
+            println!("pc = {}, rb = {}, halted = {}", pc, rel_base, dbg.computer.halted());
+        }
+        "x" => {
+            let start = addr(0)?;
+            let len = arg(1)?.unwrap_or(16).max(0) as usize;
+            let words = dbg.dump(start, len);
+
+            for (i, row) in words.chunks(8).enumerate() {
+                let row = row.iter().map(|v| format!("{:>8}", v)).collect::<Vec<_>>();
+                println!("{:04}: {}", start + i * 8, row.join(" "));
+            }
+        }
+        "l" | "list" => {
+            let start = if args.is_empty() { dbg.computer.pc() } else { addr(0)? };
+            list(dbg, start, arg(1)?.unwrap_or(8).max(0) as usize);
+        }
+        "h" | "help" => println!("{}", HELP),
+        _ => return Err(format!("unknown command {:?}, try help", cmd)),
+    }
+
+    Ok(())
+}
+
+fn report(dbg: &mut Debugger<NoIO>, stop: Stop) {
+    for val in dbg.output.drain(..) {
+        println!("output: {}", val);
+    }
+
+    match stop {
+        Stop::Step => {}
+        Stop::Breakpoint(addr) => println!("breakpoint {:04}", addr),
+        Stop::Watchpoint { addr, kind, value } => {
+            println!("watchpoint {:04}: {:?} = {}", addr, kind, value)
+        }
+        Stop::NeedInput => println!("waiting for input"),
+        Stop::Halted => println!("halted"),
+    }
+
+    list(dbg, dbg.computer.pc(), 1);
+}
+
+fn list(dbg: &Debugger<NoIO>, start: usize, count: usize) {
+    let memory = dbg.computer.memory().as_slice();
+    let mut addr = start;
+
+    for _ in 0..count {
+        let marker = if addr == dbg.computer.pc() { "=>" } else { "  " };
+        let line = match decode_at(memory, addr) {
+            Some(instruction) => Line::Instruction {
+                addr,
+                instruction,
+                params: memory[addr + 1..addr + instruction.size()].to_vec(),
+            },
+            None => Line::Data {
+                addr,
+                values: vec![dbg.computer.read(addr)],
+            },
+        };
+
+        println!("{} {}", marker, line);
+        addr += match line {
+            Line::Instruction { instruction, .. } => instruction.size(),
+            Line::Data { .. } => 1,
+        };
+    }
+}
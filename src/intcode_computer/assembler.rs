@@ -0,0 +1,296 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::intcode_computer::instructions::{Instruction, ParameterMode};
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct AssembleError {
+    pub line: usize,
+    pub msg: String,
+}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.msg)
+    }
+}
+
+impl Error for AssembleError {}
+
+enum Term {
+    Number(i64),
+    Label(String),
+}
+
+/// Sum of signed terms, e.g. `loop+2` or `-3`.
+struct Expr(Vec<(i64, Term)>);
+
+struct Operand {
+    mode: ParameterMode,
+    expr: Expr,
+}
+
+enum Statement {
+    Instruction(String, Vec<Operand>),
+    Data(Vec<Expr>),
+}
+
+/// Assembles mnemonic source into a program.
+///
+/// Each line holds optional labels (`name:`), then an instruction or a `DB`
+/// directive, then an optional `;` comment. Operands are written `[addr]`,
+/// `#imm` or `rb+off`, and any value may reference a label. A numeric label
+/// such as `0012:` asserts the current address, so disassembler listings
+/// assemble back to the original program.
+pub fn assemble(source: &str) -> Result<Vec<i64>, AssembleError> {
+    let mut labels = HashMap::new();
+    let mut statements = vec![];
+    let mut addr = 0;
+
+    for (i, line) in source.lines().enumerate() {
+        let line_no = i + 1;
+        let err = |msg: String| AssembleError { line: line_no, msg };
+
+        let mut rest = line.split(';').next().unwrap().trim();
+
+        while let Some((label, tail)) = split_label(rest) {
+            if let Ok(expected) = label.parse::<usize>() {
+                if expected != addr {
+                    return Err(err(format!("expected address {}, got {}", expected, addr)));
+                }
+            }
+            else if labels.insert(label.to_string(), addr as i64).is_some() {
+                return Err(err(format!("duplicate label {}", label)));
+            }
+            rest = tail;
+        }
+
+        if rest.is_empty() {
+            continue;
+        }
+
+        let statement = parse_statement(rest).map_err(err)?;
+        addr += match &statement {
+            Statement::Instruction(_, operands) => operands.len() + 1,
+            Statement::Data(values) => values.len(),
+        };
+        statements.push((line_no, statement));
+    }
+
+    let mut program = Vec::with_capacity(addr);
+
+    for (line_no, statement) in statements {
+        let err = |msg: String| AssembleError { line: line_no, msg };
+        let eval = |expr: &Expr| eval(expr, &labels).map_err(err);
+
+        match statement {
+            Statement::Instruction(mnemonic, operands) => {
+                let modes = operands.iter().map(|o| o.mode).collect::<Vec<_>>();
+                let instruction = instruction(&mnemonic, &modes).map_err(err)?;
+
+                program.push(instruction.into());
+                for operand in operands {
+                    program.push(eval(&operand.expr)?);
+                }
+            }
+            Statement::Data(values) => {
+                for value in values {
+                    program.push(eval(&value)?);
+                }
+            }
+        }
+    }
+
+    Ok(program)
+}
+
+fn split_label(s: &str) -> Option<(&str, &str)> {
+    let (label, tail) = s.split_once(':')?;
+    let label = label.trim();
+
+    if is_label(label) || (!label.is_empty() && label.bytes().all(|b| b.is_ascii_digit())) {
+        Some((label, tail.trim()))
+    }
+    else {
+        None
+    }
+}
+
+fn is_label(s: &str) -> bool {
+    let mut bytes = s.bytes();
+    matches!(bytes.next(), Some(b) if b.is_ascii_alphabetic() || b == b'_')
+        && bytes.all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+fn parse_statement(s: &str) -> Result<Statement, String> {
+    let (mnemonic, operands) = match s.split_once(char::is_whitespace) {
+        Some((m, o)) => (m.to_uppercase(), o.trim()),
+        None => (s.to_uppercase(), ""),
+    };
+
+    let operands = operands
+        .split(',')
+        .map(str::trim)
+        .filter(|o| !o.is_empty());
+
+    if mnemonic == "DB" {
+        Ok(Statement::Data(operands.map(parse_expr).collect::<Result<_, _>>()?))
+    }
+    else {
+        Ok(Statement::Instruction(mnemonic, operands.map(parse_operand).collect::<Result<_, _>>()?))
+    }
+}
+
+fn parse_operand(s: &str) -> Result<Operand, String> {
+    let (mode, expr) = if let Some(imm) = s.strip_prefix('#') {
+        (ParameterMode::Immediate, imm)
+    }
+    else if let Some(addr) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        (ParameterMode::Position, addr)
+    }
+    else if let Some(off) = s.strip_prefix("rb") {
+        (ParameterMode::Relative, if off.trim().is_empty() { "0" } else { off })
+    }
+    else {
+        return Err(format!("invalid operand {:?}, expected [addr], #imm or rb+off", s));
+    };
+
+    Ok(Operand { mode, expr: parse_expr(expr)? })
+}
+
+fn parse_expr(s: &str) -> Result<Expr, String> {
+    let mut terms = vec![];
+    let mut sign = 1;
+    let mut rest = s.trim();
+
+    loop {
+        if let Some(tail) = rest.strip_prefix('-') {
+            sign = -sign;
+            rest = tail.trim_start();
+            continue;
+        }
+        if let Some(tail) = rest.strip_prefix('+') {
+            rest = tail.trim_start();
+            continue;
+        }
+
+        let end = rest.find(['+', '-']).unwrap_or(rest.len());
+        let token = rest[..end].trim();
+
+        let term = if let Ok(n) = token.parse() {
+            Term::Number(n)
+        }
+        else if is_label(token) {
+            Term::Label(token.to_string())
+        }
+        else {
+            return Err(format!("invalid value {:?}", s));
+        };
+
+        terms.push((sign, term));
+        sign = 1;
+        rest = rest[end..].trim_start();
+
+        if rest.is_empty() {
+            return Ok(Expr(terms));
+        }
+        if !rest.starts_with(['+', '-']) {
+            return Err(format!("invalid value {:?}", s));
+        }
+    }
+}
+
+fn eval(expr: &Expr, labels: &HashMap<String, i64>) -> Result<i64, String> {
+    expr.0.iter().try_fold(0i64, |acc, (sign, term)| {
+        let val = match term {
+            Term::Number(n) => *n,
+            Term::Label(label) => *labels.get(label)
+                .ok_or_else(|| format!("undefined label {}", label))?,
+        };
+        sign.checked_mul(val)
+            .and_then(|val| acc.checked_add(val))
+            .ok_or_else(|| "value does not fit in 64 bits".to_string())
+    })
+}
+
+fn instruction(mnemonic: &str, modes: &[ParameterMode]) -> Result<Instruction, String> {
+    let instruction = match (mnemonic, modes) {
+        ("ADD", &[m1, m2, m3]) => Instruction::ADD((m1, m2, m3)),
+        ("MUL", &[m1, m2, m3]) => Instruction::MUL((m1, m2, m3)),
+        ("IN", &[m]) => Instruction::IN(m),
+        ("OUT", &[m]) => Instruction::OUT(m),
+        ("JNZ", &[m1, m2]) => Instruction::JNZ((m1, m2)),
+        ("JEZ", &[m1, m2]) => Instruction::JEZ((m1, m2)),
+        ("LT", &[m1, m2, m3]) => Instruction::LT((m1, m2, m3)),
+        ("EQ", &[m1, m2, m3]) => Instruction::EQ((m1, m2, m3)),
+        ("ARB", &[m]) => Instruction::ARB(m),
+        ("HLT", &[]) => Instruction::HLT,
+        ("ADD", _) | ("MUL", _) | ("LT", _) | ("EQ", _) => return Err(operand_count(mnemonic, 3, modes)),
+        ("JNZ", _) | ("JEZ", _) => return Err(operand_count(mnemonic, 2, modes)),
+        ("IN", _) | ("OUT", _) | ("ARB", _) => return Err(operand_count(mnemonic, 1, modes)),
+        ("HLT", _) => return Err(operand_count(mnemonic, 0, modes)),
+        _ => return Err(format!("unknown mnemonic {}", mnemonic)),
+    };
+
+    Ok(instruction)
+}
+
+fn operand_count(mnemonic: &str, expected: usize, modes: &[ParameterMode]) -> String {
+    format!("{} takes {} operands, got {}", mnemonic, expected, modes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::{disassemble, read_program};
+    use std::fs::File;
+
+    #[test]
+    fn test_assemble() {
+        let source = "
+            ; echo input until it is zero
+            start:  IN   [value]
+                    OUT  [value]
+                    JNZ  [value], #start
+                    ARB  #-1
+                    ADD  #2, rb-3, rb
+                    HLT
+            value:  db   0, start+1
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            vec![3,14,4,14,1005,14,0,109,-1,22101,2,-3,0,99,0,1]
+        );
+    }
+
+    #[test]
+    fn test_assemble_errors() {
+        assert_eq!(
+            assemble("IN [9]\nHLT #1").unwrap_err(),
+            AssembleError { line: 2, msg: "HLT takes 0 operands, got 1".into() }
+        );
+        assert_eq!(assemble("OUT [x]").unwrap_err().msg, "undefined label x");
+        assert_eq!(assemble("a: HLT\na: HLT").unwrap_err().msg, "duplicate label a");
+        assert_eq!(assemble("HLT\n0002: HLT").unwrap_err().msg, "expected address 2, got 1");
+        assert!(assemble("ADD 1, 2, 3").is_err());
+        assert_eq!(
+            assemble("HLT\nDB 9223372036854775807+1").unwrap_err(),
+            AssembleError { line: 2, msg: "value does not fit in 64 bits".into() }
+        );
+        assert_eq!(assemble("DB 9223372036854775807").unwrap(), vec![i64::MAX]);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for day in &["input/day05", "input/day09", "input/day13"] {
+            let program = read_program(File::open(day).unwrap());
+            let listing = disassemble(&program).to_string();
+
+            assert_eq!(assemble(&listing).unwrap(), program);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Write,
+};
+
+use crate::intcode_computer::{
+    disassembler::code_starts,
+    decode_at, successors, Instruction, Line, ParameterMode,
+};
+
+/// Straight-line run of instructions. Only the first instruction is a jump
+/// target and only the last one can jump or halt.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BasicBlock {
+    pub start: usize,
+    /// Address of every instruction in the block.
+    pub instructions: Vec<usize>,
+    /// Address just past the last instruction.
+    pub end: usize,
+    /// Start addresses of the blocks control can continue to.
+    pub successors: Vec<usize>,
+    /// The block ends in a jump whose target is only known at run time.
+    pub indirect: bool,
+}
+
+/// A position mode write from the instruction at `addr` to `target`, which
+/// lies inside an instruction found by the analysis.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CodeWrite {
+    pub addr: usize,
+    pub target: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>,
+    pub code_writes: Vec<CodeWrite>,
+}
+
+/// Splits the code reachable from address 0 into basic blocks. Jump targets
+/// are followed when they are immediate; other jumps are flagged as
+/// indirect and their targets are left out of the graph.
+pub fn control_flow_graph(program: &[i64]) -> ControlFlowGraph {
+    let starts = code_starts(program);
+    let instructions = (0..program.len())
+        .filter(|&addr| starts[addr])
+        .map(|addr| (addr, decode_at(program, addr).unwrap()))
+        .collect::<Vec<_>>();
+
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+    for &(addr, instruction) in &instructions {
+        if ends_block(instruction) {
+            leaders.extend(successors(program, addr, instruction));
+            leaders.insert(addr + instruction.size());
+        }
+    }
+
+    let mut blocks: Vec<BasicBlock> = vec![];
+    for &(addr, instruction) in &instructions {
+        let next = addr + instruction.size();
+
+        match blocks.last_mut() {
+            Some(block) if !leaders.contains(&addr) && block.successors == [addr] => {
+                block.instructions.push(addr);
+            }
+            _ => blocks.push(BasicBlock {
+                start: addr,
+                instructions: vec![addr],
+                end: next,
+                successors: vec![],
+                indirect: false,
+            }),
+        }
+
+        let block = blocks.last_mut().unwrap();
+        block.end = next;
+        block.successors = successors(program, addr, instruction)
+            .into_iter()
+            .filter(|&succ| starts.get(succ) == Some(&true))
+            .collect();
+        block.indirect = is_indirect(program, addr, instruction);
+
+        if !ends_block(instruction) && starts.get(next) != Some(&true) {
+            block.successors.clear();
+        }
+    }
+
+    ControlFlowGraph {
+        code_writes: code_writes(program, &instructions),
+        blocks,
+    }
+}
+
+fn ends_block(instruction: Instruction) -> bool {
+    matches!(instruction, Instruction::JNZ(_) | Instruction::JEZ(_) | Instruction::HLT)
+}
+
+fn is_indirect(program: &[i64], addr: usize, instruction: Instruction) -> bool {
+    match instruction {
+        Instruction::JNZ((m1, m2)) | Instruction::JEZ((m1, m2)) => {
+            let never = m1 == ParameterMode::Immediate
+                && (program[addr + 1] != 0) != matches!(instruction, Instruction::JNZ(_));
+            m2 != ParameterMode::Immediate && !never
+        }
+        _ => false,
+    }
+}
+
+fn code_writes(program: &[i64], instructions: &[(usize, Instruction)]) -> Vec<CodeWrite> {
+    let mut covered = vec![false; program.len()];
+    for &(addr, instruction) in instructions {
+        covered[addr..addr + instruction.size()].iter_mut().for_each(|c| *c = true);
+    }
+
+    instructions.iter()
+        .filter(|(_, instruction)| instruction.writes())
+        .filter(|(_, instruction)| instruction.modes().last() == Some(&ParameterMode::Position))
+        .map(|&(addr, instruction)| CodeWrite {
+            addr,
+            target: program[addr + instruction.size() - 1] as usize,
+        })
+        .filter(|write| covered.get(write.target) == Some(&true))
+        .collect()
+}
+
+impl ControlFlowGraph {
+    /// Block containing the instruction or word at `addr`.
+    pub fn block_at(&self, addr: usize) -> Option<&BasicBlock> {
+        self.blocks.iter()
+            .take_while(|block| block.start <= addr)
+            .last()
+            .filter(|block| addr < block.end)
+    }
+
+    /// Renders the graph in Graphviz DOT. Indirect jumps are drawn red and
+    /// writes into code as dashed edges.
+    pub fn to_dot(&self, program: &[i64]) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph cfg {{").unwrap();
+        writeln!(dot, "    node [shape=box, fontname=\"monospace\"];").unwrap();
+
+        for block in &self.blocks {
+            let label = block.instructions.iter()
+                .map(|&addr| {
+                    let instruction = decode_at(program, addr).unwrap();
+                    let line = Line::Instruction {
+                        addr,
+                        instruction,
+                        params: program[addr + 1..addr + instruction.size()].to_vec(),
+                    };
+                    format!("{}\\l", line)
+                })
+                .collect::<String>();
+            let color = if block.indirect { ", color=red" } else { "" };
+
+            writeln!(dot, "    b{} [label=\"{}\"{}];", block.start, label, color).unwrap();
+            for succ in &block.successors {
+                writeln!(dot, "    b{} -> b{};", block.start, succ).unwrap();
+            }
+        }
+
+        for write in &self.code_writes {
+            if let (Some(from), Some(to)) = (self.block_at(write.addr), self.block_at(write.target)) {
+                writeln!(
+                    dot,
+                    "    b{} -> b{} [style=dashed, label=\"writes {}\"];",
+                    from.start, to.start, write.target
+                ).unwrap();
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::assemble;
+
+    #[test]
+    fn test_control_flow_graph() {
+        let program = assemble("
+                   IN  [n]
+            loop:  ADD [n], #-1, [n]
+                   ADD [n], #0, [patch+1]
+                   JNZ [n], #loop
+                   JEZ [n], [n]
+            patch: OUT #0
+                   HLT
+            n:     db  0
+        ").unwrap();
+
+        let cfg = control_flow_graph(&program);
+        let summary = cfg.blocks.iter()
+            .map(|b| (b.start, b.instructions.len(), b.successors.clone(), b.indirect))
+            .collect::<Vec<_>>();
+
+        assert_eq!(summary, vec![
+            (0, 1, vec![2], false),
+            (2, 3, vec![2, 13], false),
+            (13, 1, vec![16], true),
+            (16, 2, vec![], false),
+        ]);
+        assert_eq!(cfg.code_writes, vec![CodeWrite { addr: 6, target: 17 }]);
+        assert_eq!(cfg.block_at(15).map(|b| b.start), Some(13));
+        assert_eq!(cfg.block_at(18).map(|b| b.start), Some(16));
+        assert_eq!(cfg.block_at(19), None);
+
+        let dot = cfg.to_dot(&program);
+        assert!(dot.contains("b2 -> b13;"));
+        assert!(dot.contains("b13 [label=\"0013: JEZ [19], [19]\\l\", color=red];"));
+        assert!(dot.contains("b2 -> b16 [style=dashed, label=\"writes 17\"];"));
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+};
+
+use crate::intcode_computer::{
+    disassembler::{code_starts, DATA_PER_LINE},
+    trace::{TraceRecord, TraceSink},
+    decode_at, BufferedIO, Instruction, IntcodeComputer, IntcodeError, Line, ParameterMode,
+};
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct BranchCount {
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+/// Executed instructions, data accesses and branch directions. Pass the
+/// same `Coverage` to several `run_traced` calls to merge their results.
+#[derive(Debug, Clone, Default)]
+pub struct Coverage {
+    // distinct instructions seen at each address, in order of first execution
+    executed: BTreeMap<usize, (Vec<Instruction>, u64)>,
+    reads: BTreeSet<usize>,
+    writes: BTreeSet<usize>,
+    branches: BTreeMap<usize, BranchCount>,
+}
+
+impl Coverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `program` once for every input sequence and collects coverage
+    /// over all of the runs.
+    pub fn collect<I>(program: &[i64], inputs: I) -> Result<Self, IntcodeError>
+        where I: IntoIterator, I::Item: IntoIterator<Item = i64> {
+        let mut coverage = Self::new();
+
+        for input in inputs {
+            IntcodeComputer::new(program, BufferedIO::new(input)).run_traced(&mut coverage)?;
+        }
+
+        Ok(coverage)
+    }
+
+    /// How often the instruction at `addr` was executed.
+    pub fn hits(&self, addr: usize) -> u64 {
+        self.executed.get(&addr).map_or(0, |&(_, hits)| hits)
+    }
+
+    pub fn branch(&self, addr: usize) -> Option<BranchCount> {
+        self.branches.get(&addr).copied()
+    }
+
+    pub fn was_read(&self, addr: usize) -> bool {
+        self.reads.contains(&addr)
+    }
+
+    pub fn was_written(&self, addr: usize) -> bool {
+        self.writes.contains(&addr)
+    }
+
+    /// Lists `program` with the collected coverage. Executed addresses are
+    /// decoded as the first instruction that ran there, the rest the same
+    /// way `disassemble` would. Instructions patched by the program between
+    /// executions are noted on their line.
+    pub fn annotate(&self, program: &[i64]) -> AnnotatedListing {
+        let starts = code_starts(program);
+        let is_code = |addr: usize| starts[addr] || self.executed.contains_key(&addr);
+        let access = |addr: usize| (self.was_read(addr), self.was_written(addr));
+
+        let mut lines = vec![];
+        let mut addr = 0;
+
+        while addr < program.len() {
+            let instruction = match self.executed.get(&addr) {
+                Some((instructions, _)) => Some(instructions[0]),
+                None if starts[addr] => decode_at(program, addr),
+                None => None,
+            };
+
+            let (line, hits, end) = match instruction {
+                Some(instruction) => {
+                    let end = (addr + instruction.size()).min(program.len());
+                    let line = Line::Instruction {
+                        addr,
+                        instruction,
+                        params: program[addr + 1..end].to_vec(),
+                    };
+                    (line, Some(self.hits(addr)), end)
+                }
+                None => {
+                    let len = (addr..program.len())
+                        .take(DATA_PER_LINE)
+                        .take_while(|&a| a == addr || (!is_code(a) && access(a) == access(addr)))
+                        .count();
+                    let line = Line::Data {
+                        addr,
+                        values: program[addr..addr + len].to_vec(),
+                    };
+                    (line, None, addr + len)
+                }
+            };
+
+            lines.push(AnnotatedLine {
+                line,
+                hits,
+                read: (addr..end).any(|a| self.was_read(a)),
+                written: (addr..end).any(|a| self.was_written(a)),
+                branch: self.branch(addr),
+                patched: self.executed.get(&addr)
+                    .map(|(instructions, _)| instructions[1..].to_vec())
+                    .unwrap_or_default(),
+            });
+            addr = end;
+        }
+
+        AnnotatedListing { lines }
+    }
+}
+
+impl TraceSink for Coverage {
+    fn record(&mut self, record: &TraceRecord) {
+        let (instructions, hits) = self.executed.entry(record.pc).or_default();
+        if !instructions.contains(&record.instruction) {
+            instructions.push(record.instruction);
+        }
+        *hits += 1;
+
+        self.reads.extend(&record.reads);
+        self.writes.extend(record.write.map(|(addr, _)| addr));
+
+        let taken = match record.instruction {
+            Instruction::JNZ(_) => record.operands[0] != 0,
+            Instruction::JEZ(_) => record.operands[0] == 0,
+            _ => return,
+        };
+
+        let branch = self.branches.entry(record.pc).or_default();
+        if taken {
+            branch.taken += 1;
+        }
+        else {
+            branch.not_taken += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AnnotatedLine {
+    pub line: Line,
+    /// Execution count, `None` for data.
+    pub hits: Option<u64>,
+    pub read: bool,
+    pub written: bool,
+    pub branch: Option<BranchCount>,
+    /// Other instructions executed at the same address.
+    pub patched: Vec<Instruction>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AnnotatedListing {
+    pub lines: Vec<AnnotatedLine>,
+}
+
+impl Display for AnnotatedLine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let hits = match self.hits {
+            Some(0) => "-".to_string(),
+            Some(hits) => hits.to_string(),
+            None => String::new(),
+        };
+        let marks = format!(
+            "{}{}",
+            if self.read { "r" } else { "" },
+            if self.written { "w" } else { "" },
+        );
+
+        write!(f, "{:>8} {:<2} {}", hits, marks, self.line)?;
+
+        if let Some(BranchCount { taken, not_taken }) = self.branch {
+            write!(f, "  ; taken {}, not taken {}", taken, not_taken)?;
+        }
+        if !self.patched.is_empty() {
+            let mnemonics = self.patched.iter().map(Instruction::mnemonic).collect::<Vec<_>>();
+            write!(f, "  ; also ran as {}", mnemonics.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for AnnotatedListing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let instructions = self.lines.iter().filter(|l| l.hits.is_some()).collect::<Vec<_>>();
+        let executed = instructions.iter().filter(|l| l.hits != Some(0)).count();
+        // a jump with an immediate condition can only ever go one way
+        let possible = instructions.iter()
+            .map(|l| match l.line {
+                Line::Instruction { instruction: Instruction::JNZ((mode, _)), .. }
+                    | Line::Instruction { instruction: Instruction::JEZ((mode, _)), .. }
+                    if mode == ParameterMode::Immediate => 1,
+                Line::Instruction { instruction: Instruction::JNZ(_), .. }
+                    | Line::Instruction { instruction: Instruction::JEZ(_), .. } => 2,
+                _ if l.branch.is_some() => 2,
+                _ => 0,
+            })
+            .sum::<usize>();
+        let directions = instructions.iter()
+            .filter_map(|l| l.branch)
+            .map(|b| (b.taken > 0) as usize + (b.not_taken > 0) as usize)
+            .sum::<usize>();
+
+        writeln!(
+            f,
+            "{} of {} instructions executed, {} of {} branch directions taken",
+            executed,
+            instructions.len(),
+            directions,
+            possible,
+        )?;
+
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::assemble;
+
+    #[test]
+    fn test_coverage() {
+        let program = assemble("
+                  IN  [x]
+                  JEZ [x], #zero
+                  OUT [x]
+                  JNZ #1, #zero
+            zero: HLT
+            x:    db  0
+                  db  1, 2
+        ").unwrap();
+
+        let coverage = Coverage::collect(&program, vec![vec![0]]).unwrap();
+        assert_eq!(coverage.hits(5), 0);
+        assert_eq!(coverage.branch(2), Some(BranchCount { taken: 1, not_taken: 0 }));
+
+        let coverage = Coverage::collect(&program, vec![vec![0], vec![5]]).unwrap();
+        assert_eq!(
+            coverage.annotate(&program).to_string(),
+            "5 of 5 instructions executed, 3 of 3 branch directions taken\n       \
+                    2    0000: IN  [11]\n       \
+                    2    0002: JEZ [11], #10  ; taken 1, not taken 1\n       \
+                    1    0005: OUT [11]\n       \
+                    1    0007: JNZ #1, #10  ; taken 1, not taken 0\n       \
+                    2    0010: HLT\n         \
+                      rw 0011: DB  0\n            \
+                         0012: DB  1, 2\n"
+        );
+    }
+}
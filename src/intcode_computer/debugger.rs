@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::intcode_computer::{DenseMemory, Event, IntcodeComputer, IntcodeError, Memory, IO};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    Access,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Stop {
+    Step,
+    Breakpoint(usize),
+    Watchpoint {
+        addr: usize,
+        kind: WatchKind,
+        value: i64,
+    },
+    NeedInput,
+    Halted,
+}
+
+/// Drives an `IntcodeComputer` through the event API, stopping on
+/// breakpoints and watchpoints. Input is taken from `input` and output is
+/// collected in `output`; the computer's own IO is not used.
+pub struct Debugger<T, M = DenseMemory>
+    where T: IO, M: Memory<Word = i64> {
+    pub computer: IntcodeComputer<T, M>,
+    pub input: VecDeque<i64>,
+    pub output: Vec<i64>,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: BTreeMap<usize, WatchKind>,
+    // pc of the last stop, so resuming doesn't stop at the same breakpoint
+    stopped_at: Option<usize>,
+}
+
+impl<T, M> Debugger<T, M>
+    where T: IO, M: Memory<Word = i64> {
+    pub fn new(computer: IntcodeComputer<T, M>) -> Self {
+        Self {
+            computer,
+            input: VecDeque::new(),
+            output: vec![],
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+            stopped_at: None,
+        }
+    }
+
+    pub fn add_breakpoint(&mut self, addr: usize) {
+        self.breakpoints.insert(addr);
+    }
+
+    pub fn remove_breakpoint(&mut self, addr: usize) -> bool {
+        self.breakpoints.remove(&addr)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &usize> {
+        self.breakpoints.iter()
+    }
+
+    pub fn add_watchpoint(&mut self, addr: usize, kind: WatchKind) {
+        self.watchpoints.insert(addr, kind);
+    }
+
+    pub fn remove_watchpoint(&mut self, addr: usize) -> bool {
+        self.watchpoints.remove(&addr).is_some()
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = (&usize, &WatchKind)> {
+        self.watchpoints.iter()
+    }
+
+    /// Returns `(pc, rel_base)`.
+    pub fn registers(&self) -> (usize, i64) {
+        (self.computer.pc(), self.computer.rel_base())
+    }
+
+    pub fn dump(&self, addr: usize, len: usize) -> Vec<i64> {
+        (addr..addr + len).map(|a| self.computer.read(a)).collect()
+    }
+
+    /// Executes a single instruction.
+    pub fn step(&mut self) -> Result<Stop, IntcodeError> {
+        let stop = self.execute()?.unwrap_or(Stop::Step);
+        Ok(self.stop(stop))
+    }
+
+    /// Executes instructions until a breakpoint or watchpoint is hit, the
+    /// program halts or it needs input that hasn't been queued. A breakpoint
+    /// at the current pc is hit right away, unless the debugger already
+    /// stopped there.
+    pub fn resume(&mut self) -> Result<Stop, IntcodeError> {
+        let pc = self.computer.pc();
+        if self.breakpoints.contains(&pc) && self.stopped_at != Some(pc) {
+            return Ok(self.stop(Stop::Breakpoint(pc)));
+        }
+
+        loop {
+            if let Some(stop) = self.execute()? {
+                return Ok(self.stop(stop));
+            }
+
+            let pc = self.computer.pc();
+            if self.breakpoints.contains(&pc) {
+                return Ok(self.stop(Stop::Breakpoint(pc)));
+            }
+        }
+    }
+
+    fn stop(&mut self, stop: Stop) -> Stop {
+        self.stopped_at = Some(self.computer.pc());
+        stop
+    }
+
+    fn execute(&mut self) -> Result<Option<Stop>, IntcodeError> {
+        let access = self.computer.next_access()?;
+
+        let mut event = self.computer.step()?;
+        if event == Some(Event::NeedInput) {
+            if let Some(val) = self.input.pop_front() {
+                self.computer.provide_input(val);
+                event = self.computer.step()?;
+            }
+        }
+
+        match event {
+            Some(Event::NeedInput) => return Ok(Some(Stop::NeedInput)),
+            Some(Event::Halted) => return Ok(Some(Stop::Halted)),
+            Some(Event::Output(val)) => self.output.push(val),
+            None => {}
+        }
+
+        Ok(self.watch_hit(&access.reads, &access.write))
+    }
+
+    fn watch_hit(&self, reads: &[usize], write: &Option<usize>) -> Option<Stop> {
+        let hit = |addr: usize, kind: WatchKind| Stop::Watchpoint {
+            addr,
+            kind,
+            value: self.computer.read(addr),
+        };
+
+        if let Some(addr) = *write {
+            match self.watchpoints.get(&addr) {
+                Some(WatchKind::Write) | Some(WatchKind::Access) => return Some(hit(addr, WatchKind::Write)),
+                _ => {}
+            }
+        }
+
+        reads.iter()
+            .find(|addr| matches!(self.watchpoints.get(addr), Some(WatchKind::Read) | Some(WatchKind::Access)))
+            .map(|&addr| hit(addr, WatchKind::Read))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::{assemble, NoIO};
+
+    fn debugger(source: &str) -> Debugger<NoIO> {
+        let program = assemble(source).unwrap();
+        Debugger::new(IntcodeComputer::new(&program, NoIO))
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut dbg = debugger("
+            loop: IN  [x]
+                  OUT [x]
+                  JNZ [x], #loop
+                  HLT
+            x:    db  0
+        ");
+        dbg.input.extend(vec![3, 2, 0]);
+        dbg.add_breakpoint(2);
+
+        assert_eq!(dbg.resume().unwrap(), Stop::Breakpoint(2));
+        assert_eq!(dbg.output, vec![]);
+        assert_eq!(dbg.resume().unwrap(), Stop::Breakpoint(2));
+        assert_eq!(dbg.output, vec![3]);
+
+        assert!(dbg.remove_breakpoint(2));
+        assert_eq!(dbg.resume().unwrap(), Stop::Halted);
+        assert_eq!(dbg.output, vec![3, 2, 0]);
+    }
+
+    #[test]
+    fn test_breakpoint_at_pc() {
+        let mut dbg = debugger("
+            loop: IN  [x]
+                  OUT [x]
+                  JNZ [x], #loop
+                  HLT
+            x:    db  0
+        ");
+        dbg.input.extend(vec![3, 0]);
+        dbg.add_breakpoint(0);
+
+        assert_eq!(dbg.resume().unwrap(), Stop::Breakpoint(0));
+        assert_eq!(dbg.output, vec![]);
+        assert_eq!(dbg.resume().unwrap(), Stop::Breakpoint(0));
+        assert_eq!(dbg.output, vec![3]);
+
+        // stepping onto a breakpoint counts as stopping there
+        dbg.add_breakpoint(2);
+        assert_eq!(dbg.step().unwrap(), Stop::Step);
+        assert_eq!(dbg.registers(), (2, 0));
+        assert_eq!(dbg.resume().unwrap(), Stop::Halted);
+        assert_eq!(dbg.output, vec![3, 0]);
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let source = "
+            ADD #1, #2, [x]
+            IN  [y]
+            MUL [x], [y], [y]
+            HLT
+            x: db 0
+            y: db 0
+        ";
+
+        let mut dbg = debugger(source);
+        dbg.add_watchpoint(11, WatchKind::Read);
+        dbg.add_watchpoint(12, WatchKind::Write);
+
+        assert_eq!(dbg.resume().unwrap(), Stop::NeedInput);
+        assert_eq!(dbg.registers(), (4, 0));
+
+        dbg.input.push_back(5);
+        assert_eq!(dbg.resume().unwrap(), Stop::Watchpoint { addr: 12, kind: WatchKind::Write, value: 5 });
+        assert_eq!(dbg.resume().unwrap(), Stop::Watchpoint { addr: 12, kind: WatchKind::Write, value: 15 });
+        assert_eq!(dbg.dump(11, 3), vec![3, 15, 0]);
+
+        assert!(dbg.remove_watchpoint(12));
+        assert_eq!(dbg.resume().unwrap(), Stop::Halted);
+
+        let mut dbg = debugger(source);
+        dbg.add_watchpoint(11, WatchKind::Access);
+        assert_eq!(dbg.step().unwrap(), Stop::Watchpoint { addr: 11, kind: WatchKind::Write, value: 3 });
+        assert_eq!(dbg.step().unwrap(), Stop::NeedInput);
+    }
+}
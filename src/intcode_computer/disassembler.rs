@@ -0,0 +1,280 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::intcode_computer::instructions::{Instruction, ParameterMode};
+
+pub(crate) const DATA_PER_LINE: usize = 8;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Line {
+    Instruction {
+        addr: usize,
+        instruction: Instruction,
+        params: Vec<i64>,
+    },
+    Data {
+        addr: usize,
+        values: Vec<i64>,
+    },
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Listing {
+    pub lines: Vec<Line>,
+}
+
+/// Disassembles a program, decoding only words reachable as code from
+/// address 0 or from a return address pushed before a call. Everything else
+/// is listed as data.
+pub fn disassemble(program: &[i64]) -> Listing {
+    let starts = code_starts(program);
+    let mut lines = vec![];
+    let mut addr = 0;
+
+    while addr < program.len() {
+        if starts[addr] {
+            let instruction = decode_at(program, addr).unwrap();
+            let size = instruction.size();
+
+            lines.push(Line::Instruction {
+                addr,
+                instruction,
+                params: program[addr + 1..addr + size].to_vec(),
+            });
+            addr += size;
+        }
+        else {
+            let values = program[addr..]
+                .iter()
+                .zip(&starts[addr..])
+                .take(DATA_PER_LINE)
+                .take_while(|(_, &start)| !start)
+                .map(|(&v, _)| v)
+                .collect::<Vec<_>>();
+
+            let len = values.len();
+            lines.push(Line::Data { addr, values });
+            addr += len;
+        }
+    }
+
+    Listing { lines }
+}
+
+/// Decodes the word at `addr` if it is a canonically encoded instruction that
+/// fits in the program and doesn't write to an immediate parameter.
+pub fn decode_at(program: &[i64], addr: usize) -> Option<Instruction> {
+    let word = *program.get(addr)?;
+    let instruction = Instruction::try_from(word).ok()?;
+
+    if i64::from(instruction) != word || addr + instruction.size() > program.len() {
+        return None;
+    }
+
+    if instruction.writes() && instruction.modes().last() == Some(&ParameterMode::Immediate) {
+        return None;
+    }
+
+    Some(instruction)
+}
+
+/// Statically known successors of the instruction at `addr`: the fall
+/// through address unless the instruction halts or always jumps, plus the
+/// jump target when it is immediate.
+pub fn successors(program: &[i64], addr: usize, instruction: Instruction) -> Vec<usize> {
+    let next = addr + instruction.size();
+
+    match instruction {
+        Instruction::HLT => vec![],
+        Instruction::JNZ((m1, m2)) | Instruction::JEZ((m1, m2)) => {
+            let cond = program[addr + 1];
+            let target = program[addr + 2];
+            let always = m1 == ParameterMode::Immediate && match instruction {
+                Instruction::JNZ(_) => cond != 0,
+                _ => cond == 0,
+            };
+            let never = m1 == ParameterMode::Immediate && !always;
+
+            let mut succ = vec![];
+            if !never && m2 == ParameterMode::Immediate && target >= 0 {
+                succ.push(target as usize);
+            }
+            if !always {
+                succ.push(next);
+            }
+            succ
+        }
+        _ => vec![next],
+    }
+}
+
+/// Immediate operands of an instruction followed by an unconditional jump,
+/// like the return address a caller stores before jumping to a subroutine.
+fn return_sites(program: &[i64], addr: usize, instruction: Instruction) -> Vec<usize> {
+    let next = addr + instruction.size();
+    let calls = match decode_at(program, next) {
+        Some(jump @ Instruction::JNZ(_)) | Some(jump @ Instruction::JEZ(_)) => {
+            !successors(program, next, jump).contains(&(next + jump.size()))
+        }
+        _ => false,
+    };
+
+    if !instruction.writes() || !calls {
+        return vec![];
+    }
+
+    instruction.modes()
+        .into_iter()
+        .zip(&program[addr + 1..next - 1])
+        .filter(|&(mode, &param)| mode == ParameterMode::Immediate && param >= 0)
+        .map(|(_, &param)| param as usize)
+        .collect()
+}
+
+pub(crate) fn code_starts(program: &[i64]) -> Vec<bool> {
+    let mut starts = vec![false; program.len()];
+    let mut covered = vec![false; program.len()];
+    let mut stack = vec![0];
+    // only tried once everything reachable by jumps has been decoded, so
+    // a constant that happens to look like an address can't claim real code
+    let mut returns = vec![];
+
+    while let Some(addr) = stack.pop().or_else(|| returns.pop()) {
+        if addr >= program.len() || covered[addr] {
+            continue;
+        }
+
+        let instruction = match decode_at(program, addr) {
+            Some(instruction) => instruction,
+            None => continue,
+        };
+
+        let end = addr + instruction.size();
+        if covered[addr..end].iter().any(|&c| c) {
+            continue;
+        }
+
+        starts[addr] = true;
+        covered[addr..end].iter_mut().for_each(|c| *c = true);
+        stack.extend(successors(program, addr, instruction));
+        returns.extend(return_sites(program, addr, instruction));
+    }
+
+    starts
+}
+
+pub fn format_param(mode: ParameterMode, param: i64) -> String {
+    match mode {
+        ParameterMode::Position => format!("[{}]", param),
+        ParameterMode::Immediate => format!("#{}", param),
+        ParameterMode::Relative if param < 0 => format!("rb{}", param),
+        ParameterMode::Relative => format!("rb+{}", param),
+    }
+}
+
+impl Line {
+    pub fn addr(&self) -> usize {
+        match self {
+            Line::Instruction { addr, .. } | Line::Data { addr, .. } => *addr,
+        }
+    }
+}
+
+impl Display for Line {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}: ", self.addr())?;
+
+        match self {
+            Line::Instruction { instruction, params, .. } => {
+                let operands = instruction.modes()
+                    .into_iter()
+                    .zip(params)
+                    .map(|(mode, &param)| format_param(mode, param))
+                    .collect::<Vec<_>>();
+
+                if operands.is_empty() {
+                    write!(f, "{}", instruction.mnemonic())
+                }
+                else {
+                    write!(f, "{:<4}{}", instruction.mnemonic(), operands.join(", "))
+                }
+            }
+            Line::Data { values, .. } => {
+                let values = values.iter().map(i64::to_string).collect::<Vec<_>>();
+                write!(f, "DB  {}", values.join(", "))
+            }
+        }
+    }
+}
+
+impl Display for Listing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::assemble;
+
+    #[test]
+    fn test_disassemble() {
+        let program = vec![3,9,8,9,10,9,4,9,99,-1,8];
+
+        assert_eq!(
+            disassemble(&program).to_string(),
+            "0000: IN  [9]\n\
+             0002: EQ  [9], [10], [9]\n\
+             0006: OUT [9]\n\
+             0008: HLT\n\
+             0009: DB  -1, 8\n"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_modes() {
+        let program = vec![109,-1,21101,5,6,3,204,2,1106,0,14,1,2,3,99];
+
+        assert_eq!(
+            disassemble(&program).to_string(),
+            "0000: ARB #-1\n\
+             0002: ADD #5, #6, rb+3\n\
+             0006: OUT rb+2\n\
+             0008: JEZ #0, #14\n\
+             0011: DB  1, 2, 3\n\
+             0014: HLT\n"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_calls() {
+        let program = assemble("
+                    ARB #100
+                    ADD #ret, #0, rb+0
+                    JNZ #1, #double
+            ret:    OUT [x]
+                    HLT
+            double: MUL [x], #2, [x]
+                    JEZ #0, rb+0
+            x:      DB  21
+        ").unwrap();
+
+        assert_eq!(
+            disassemble(&program).to_string(),
+            "0000: ARB #100\n\
+             0002: ADD #9, #0, rb+0\n\
+             0006: JNZ #1, #12\n\
+             0009: OUT [19]\n\
+             0011: HLT\n\
+             0012: MUL [19], #2, [19]\n\
+             0016: JEZ #0, rb+0\n\
+             0019: DB  21\n"
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::intcode_computer::instructions::ParameterMode;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+    UnknownOpcode(i64),
+    UnknownParameterMode(i64),
+    ImmediateDestination,
+    AddressOutOfRange(usize),
+    NegativeAddress(i64),
+    StepLimitExceeded(u64),
+    DeadlineExceeded,
+    Overflow,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct IntcodeError {
+    pub pc: usize,
+    pub word: i64,
+    pub modes: [Option<ParameterMode>; 3],
+    pub kind: ErrorKind,
+}
+
+impl IntcodeError {
+    pub fn new(pc: usize, word: i64, kind: ErrorKind) -> Self {
+        let mode = |n| ParameterMode::try_from(n).ok();
+
+        Self {
+            pc,
+            word,
+            modes: [
+                mode((word / 100) % 10),
+                mode((word / 1000) % 10),
+                mode((word / 10000) % 10),
+            ],
+            kind,
+        }
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownOpcode(opcode) => write!(f, "unknown opcode {}", opcode),
+            ErrorKind::UnknownParameterMode(mode) => write!(f, "unknown parameter mode {}", mode),
+            ErrorKind::ImmediateDestination => write!(f, "immediate mode destination"),
+            ErrorKind::AddressOutOfRange(addr) => write!(f, "write to address {} beyond the memory limit", addr),
+            ErrorKind::NegativeAddress(addr) => write!(f, "negative address {}", addr),
+            ErrorKind::StepLimitExceeded(steps) => write!(f, "step limit of {} instructions exceeded", steps),
+            ErrorKind::DeadlineExceeded => write!(f, "deadline exceeded"),
+            ErrorKind::Overflow => write!(f, "value does not fit the word size"),
+        }
+    }
+}
+
+impl Display for IntcodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at pc {} (word {}, modes {:?})", self.kind, self.pc, self.word, self.modes)
+    }
+}
+
+impl Error for IntcodeError {}
@@ -0,0 +1,450 @@
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::intcode_computer::{
+    BufferedIO, Engine, ErrorKind, Instruction, IntcodeComputer, ParameterMode, Status,
+};
+
+const MAX_STEPS: u64 = 2000;
+const MAX_ADDRESS: usize = 4095;
+// runs without a max address skip cases that write beyond this, they
+// would only measure the allocator
+const UNBOUNDED_WORDS: usize = 1 << 20;
+
+/// A program together with the input it is fed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Case {
+    pub program: Vec<i64>,
+    pub input: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Failure {
+    pub seed: u64,
+    pub case: Case,
+    pub reason: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seed {}: {}\nprogram: {:?}\ninput: {:?}",
+            self.seed, self.reason, self.case.program, self.case.input
+        )
+    }
+}
+
+/// Checks the cases generated from `seed..seed + iterations`, returning the
+/// first failure shrunk to a minimal case.
+pub fn fuzz(seed: u64, iterations: u64) -> Result<(), Failure> {
+    for seed in seed..seed + iterations {
+        let case = generate(seed);
+
+        if check(&case).is_err() {
+            let case = shrink(case, |case| check(case).is_err());
+            let reason = check(&case).unwrap_err();
+            return Err(Failure { seed, case, reason });
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs `case` on both engines, with and without a max address, and on the
+/// reference interpreter and checks that they agree and that the VM's
+/// invariants hold.
+pub fn check(case: &Case) -> Result<(), String> {
+    for &max_address in &[Some(MAX_ADDRESS), None] {
+        let expected = match reference(case, max_address) {
+            Some(expected) => expected,
+            None => continue,
+        };
+
+        for &engine in &[Engine::Interpreter, Engine::Cached] {
+            let config = match max_address {
+                Some(max) => format!("{:?} engine with max address {}", engine, max),
+                None => format!("{:?} engine without max address", engine),
+            };
+            let actual = panic::catch_unwind(AssertUnwindSafe(|| run(case, engine, max_address)))
+                .map_err(|_| format!("{} panicked", config))?;
+
+            if actual.halted != (actual.result == Ok(Status::Halted)) {
+                return Err(format!("{}: halted is {} after {:?}", config, actual.halted, actual.result));
+            }
+            if actual.memory.len() < case.program.len() {
+                return Err(format!("{}: memory shrank to {} words", config, actual.memory.len()));
+            }
+            if actual != expected {
+                return Err(format!("{}: got {:?}, expected {:?}", config, actual, expected));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Outcome {
+    result: Result<Status, (usize, ErrorKind)>,
+    halted: bool,
+    steps: u64,
+    output: Vec<i64>,
+    memory: Vec<i64>,
+}
+
+fn run(case: &Case, engine: Engine, max_address: Option<usize>) -> Outcome {
+    let mut computer = IntcodeComputer::new(&case.program, BufferedIO::new(case.input.clone()))
+        .with_engine(engine)
+        .with_step_limit(MAX_STEPS);
+    if let Some(max) = max_address {
+        computer = computer.with_max_address(max);
+    }
+
+    let result = computer.run().map_err(|e| (e.pc, e.kind));
+
+    Outcome {
+        result,
+        halted: computer.halted(),
+        steps: computer.steps(),
+        output: computer.io.output.clone(),
+        memory: computer.memory().as_slice().to_vec(),
+    }
+}
+
+/// Deliberately naive interpreter working directly on the digits of each
+/// word, kept separate from the VM so the two can be compared.
+struct Reference {
+    memory: Vec<i64>,
+    input: Vec<i64>,
+    output: Vec<i64>,
+    pc: usize,
+    rel_base: i64,
+    steps: u64,
+    max_address: Option<usize>,
+    // set when a run without max address wrote beyond `UNBOUNDED_WORDS`
+    too_far: bool,
+}
+
+fn reference(case: &Case, max_address: Option<usize>) -> Option<Outcome> {
+    let mut vm = Reference {
+        memory: case.program.clone(),
+        input: case.input.iter().rev().copied().collect(),
+        output: vec![],
+        pc: 0,
+        rel_base: 0,
+        steps: 0,
+        max_address,
+        too_far: false,
+    };
+
+    let result = loop {
+        if vm.steps >= MAX_STEPS {
+            break Err((vm.pc, ErrorKind::StepLimitExceeded(vm.steps)));
+        }
+
+        match vm.step() {
+            Ok(None) => vm.steps += 1,
+            Ok(Some(Status::Halted)) => {
+                vm.steps += 1;
+                break Ok(Status::Halted);
+            }
+            Ok(Some(status)) => break Ok(status),
+            Err(kind) => break Err((vm.pc, kind)),
+        }
+    };
+
+    if vm.too_far {
+        return None;
+    }
+
+    Some(Outcome {
+        halted: result == Ok(Status::Halted),
+        result,
+        steps: vm.steps,
+        output: vm.output,
+        memory: vm.memory,
+    })
+}
+
+impl Reference {
+    fn read(&self, addr: usize) -> i64 {
+        self.memory.get(addr).copied().unwrap_or(0)
+    }
+
+    fn store(&mut self, addr: usize, val: i64) -> Result<(), ErrorKind> {
+        if self.max_address.is_some_and(|max| addr > max) {
+            return Err(ErrorKind::AddressOutOfRange(addr));
+        }
+        if addr >= UNBOUNDED_WORDS {
+            self.too_far = true;
+            return Err(ErrorKind::AddressOutOfRange(addr));
+        }
+        if addr >= self.memory.len() {
+            self.memory.resize(addr + 1, 0);
+        }
+        self.memory[addr] = val;
+        Ok(())
+    }
+
+    fn addr(&self, mode: i64, param: i64) -> Result<usize, ErrorKind> {
+        match mode {
+            0 => to_addr(param),
+            1 => Err(ErrorKind::ImmediateDestination),
+            _ => to_addr(self.rel_base.checked_add(param).ok_or(ErrorKind::Overflow)?),
+        }
+    }
+
+    fn val(&self, mode: i64, param: i64) -> Result<i64, ErrorKind> {
+        match mode {
+            1 => Ok(param),
+            _ => self.addr(mode, param).map(|a| self.read(a)),
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<Status>, ErrorKind> {
+        let word = self.read(self.pc);
+        let opcode = word % 100;
+        let arity = match opcode {
+            1 | 2 | 7 | 8 => 3,
+            5 | 6 => 2,
+            3 | 4 | 9 => 1,
+            99 => 0,
+            _ => return Err(ErrorKind::UnknownOpcode(opcode)),
+        };
+
+        let mut modes = [0; 3];
+        let mut params = [0; 3];
+        for i in 0..arity {
+            modes[i] = (word / [100, 1000, 10000][i]) % 10;
+            if !(0..=2).contains(&modes[i]) {
+                return Err(ErrorKind::UnknownParameterMode(modes[i]));
+            }
+            params[i] = self.read(self.pc + i + 1);
+        }
+
+        match opcode {
+            1 | 2 | 7 | 8 => {
+                let a = self.val(modes[0], params[0])?;
+                let b = self.val(modes[1], params[1])?;
+                let dest = self.addr(modes[2], params[2])?;
+                let result = match opcode {
+                    1 => a.checked_add(b).ok_or(ErrorKind::Overflow)?,
+                    2 => a.checked_mul(b).ok_or(ErrorKind::Overflow)?,
+                    7 => (a < b) as i64,
+                    _ => (a == b) as i64,
+                };
+                self.store(dest, result)?;
+                self.pc += 4;
+            }
+            3 => {
+                let dest = self.addr(modes[0], params[0])?;
+                match self.input.pop() {
+                    Some(val) => self.store(dest, val)?,
+                    None => return Ok(Some(Status::BlockedOnInput)),
+                }
+                self.pc += 2;
+            }
+            4 => {
+                let val = self.val(modes[0], params[0])?;
+                self.output.push(val);
+                self.pc += 2;
+            }
+            5 | 6 => {
+                let cond = self.val(modes[0], params[0])?;
+                let target = self.val(modes[1], params[1])?;
+                if (cond != 0) == (opcode == 5) {
+                    self.pc = to_addr(target)?;
+                }
+                else {
+                    self.pc += 3;
+                }
+            }
+            9 => {
+                let val = self.val(modes[0], params[0])?;
+                self.rel_base = self.rel_base.checked_add(val).ok_or(ErrorKind::Overflow)?;
+                self.pc += 2;
+            }
+            _ => return Ok(Some(Status::Halted)),
+        }
+
+        Ok(None)
+    }
+}
+
+fn to_addr(val: i64) -> Result<usize, ErrorKind> {
+    usize::try_from(val).map_err(|_| ErrorKind::NegativeAddress(val))
+}
+
+/// xorshift64*, good enough to pick instructions.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo + 1) as u64) as i64
+    }
+
+    /// Immediate mode is never picked for destinations.
+    fn mode(&mut self, dest: bool) -> ParameterMode {
+        match self.below(if dest { 2 } else { 3 }) {
+            0 => ParameterMode::Position,
+            1 => ParameterMode::Relative,
+            _ => ParameterMode::Immediate,
+        }
+    }
+}
+
+/// Builds a random program of mostly valid instructions followed by a data
+/// area. Addresses mostly point into the program, with the occasional
+/// negative one or one beyond `MAX_ADDRESS`, jumps target instruction
+/// starts and the occasional huge immediate exercises overflow checks.
+/// Some programs get an instruction with an unknown opcode or mode.
+pub fn generate(seed: u64) -> Case {
+    let mut rng = Rng::new(seed);
+    let count = rng.range(1, 16) as usize;
+
+    let instructions = (0..count)
+        .map(|_| {
+            let rng = &mut rng;
+            match rng.below(10) {
+                0 => Instruction::ADD((rng.mode(false), rng.mode(false), rng.mode(true))),
+                1 => Instruction::MUL((rng.mode(false), rng.mode(false), rng.mode(true))),
+                2 => Instruction::IN(rng.mode(true)),
+                3 => Instruction::OUT(rng.mode(false)),
+                4 => Instruction::JNZ((rng.mode(false), ParameterMode::Immediate)),
+                5 => Instruction::JEZ((rng.mode(false), ParameterMode::Immediate)),
+                6 => Instruction::LT((rng.mode(false), rng.mode(false), rng.mode(true))),
+                7 => Instruction::EQ((rng.mode(false), rng.mode(false), rng.mode(true))),
+                8 => Instruction::ARB(rng.mode(false)),
+                _ => Instruction::HLT,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let starts = instructions.iter()
+        .scan(0, |addr, instruction| {
+            let start = *addr;
+            *addr += instruction.size();
+            Some(start as i64)
+        })
+        .collect::<Vec<_>>();
+    let code_len = instructions.iter().map(Instruction::size).sum::<usize>() as i64;
+    let data_len = rng.range(1, 8);
+
+    let mut program = vec![];
+    for instruction in &instructions {
+        program.push(i64::from(*instruction));
+
+        let jump = matches!(instruction, Instruction::JNZ(_) | Instruction::JEZ(_));
+        for (i, mode) in instruction.modes().into_iter().enumerate() {
+            let param = match mode {
+                _ if jump && i == 1 => starts[rng.below(starts.len() as u64) as usize],
+                ParameterMode::Position => match rng.below(10) {
+                    0 => rng.range(-8, -1),
+                    1 => rng.range(MAX_ADDRESS as i64 + 1, 2 * MAX_ADDRESS as i64),
+                    _ => rng.range(0, code_len + data_len - 1),
+                },
+                ParameterMode::Relative => rng.range(-4, 8),
+                ParameterMode::Immediate if rng.below(20) == 0 => rng.range(1 << 40, 1 << 62),
+                ParameterMode::Immediate => rng.range(-5, 20),
+            };
+            program.push(param);
+        }
+    }
+    program.extend((0..data_len).map(|_| rng.range(-10, 10)));
+
+    if rng.below(4) == 0 {
+        let at = starts[rng.below(starts.len() as u64) as usize] as usize;
+        let word = program[at];
+        program[at] = match rng.below(2) {
+            // opcodes 10 to 98 are all unknown
+            0 => word / 100 * 100 + rng.range(10, 98),
+            _ => word - (word / 100 % 10) * 100 + rng.range(3, 9) * 100,
+        };
+    }
+
+    let input = (0..rng.below(4)).map(|_| rng.range(-10, 10)).collect();
+
+    Case { program, input }
+}
+
+/// Repeatedly drops inputs, removes words and shrinks values towards zero
+/// while `fails` still holds.
+pub fn shrink<F: Fn(&Case) -> bool>(mut case: Case, fails: F) -> Case {
+    loop {
+        let candidates = shrink_candidates(&case);
+        match candidates.into_iter().find(|candidate| fails(candidate)) {
+            Some(smaller) => case = smaller,
+            None => return case,
+        }
+    }
+}
+
+fn shrink_candidates(case: &Case) -> Vec<Case> {
+    let mut candidates = vec![];
+
+    for i in 0..case.input.len() {
+        let mut input = case.input.clone();
+        input.remove(i);
+        candidates.push(Case { program: case.program.clone(), input });
+    }
+
+    for i in (0..case.program.len()).rev() {
+        let mut program = case.program.clone();
+        program.remove(i);
+        candidates.push(Case { program, input: case.input.clone() });
+    }
+
+    for (i, &word) in case.program.iter().enumerate() {
+        for smaller in [0, word / 2] {
+            if smaller != word {
+                let mut program = case.program.clone();
+                program[i] = smaller;
+                candidates.push(Case { program, input: case.input.clone() });
+            }
+        }
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzz() {
+        if let Err(failure) = fuzz(0, 2000) {
+            panic!("{}", failure);
+        }
+    }
+
+    #[test]
+    fn test_shrink() {
+        let case = Case {
+            program: vec![1101, 3, 4, 9, 104, 7, 99, 5],
+            input: vec![1, 2],
+        };
+
+        let shrunk = shrink(case, |case| case.program.contains(&104));
+        assert_eq!(shrunk, Case { program: vec![104], input: vec![] });
+    }
+}
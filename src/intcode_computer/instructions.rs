@@ -1,4 +1,9 @@
-#[derive(Debug, Eq, PartialEq)]
+use std::convert::{TryFrom, TryInto};
+
+use crate::intcode_computer::error::ErrorKind;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Instruction {
     ADD((ParameterMode, ParameterMode, ParameterMode)),
     MUL((ParameterMode, ParameterMode, ParameterMode)),
@@ -17,43 +22,133 @@ pub enum Instruction {
     HLT
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum ParameterMode {
     Position,
     Immediate,
     Relative,
 }
 
-impl From<i64> for ParameterMode {
-    fn from(n: i64) -> Self {
+impl Instruction {
+    pub fn opcode(&self) -> i64 {
+        match self {
+            Instruction::ADD(_) => 1,
+            Instruction::MUL(_) => 2,
+            Instruction::IN(_) => 3,
+            Instruction::OUT(_) => 4,
+            Instruction::JNZ(_) => 5,
+            Instruction::JEZ(_) => 6,
+            Instruction::LT(_) => 7,
+            Instruction::EQ(_) => 8,
+            Instruction::ARB(_) => 9,
+            Instruction::HLT => 99,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Instruction::ADD(_) => "ADD",
+            Instruction::MUL(_) => "MUL",
+            Instruction::IN(_) => "IN",
+            Instruction::OUT(_) => "OUT",
+            Instruction::JNZ(_) => "JNZ",
+            Instruction::JEZ(_) => "JEZ",
+            Instruction::LT(_) => "LT",
+            Instruction::EQ(_) => "EQ",
+            Instruction::ARB(_) => "ARB",
+            Instruction::HLT => "HLT",
+        }
+    }
+
+    /// Parameter modes in operand order.
+    pub fn modes(&self) -> Vec<ParameterMode> {
+        match *self {
+            Instruction::ADD((m1, m2, m3))
+            | Instruction::MUL((m1, m2, m3))
+            | Instruction::LT((m1, m2, m3))
+            | Instruction::EQ((m1, m2, m3)) => vec![m1, m2, m3],
+            Instruction::JNZ((m1, m2))
+            | Instruction::JEZ((m1, m2)) => vec![m1, m2],
+            Instruction::IN(m)
+            | Instruction::OUT(m)
+            | Instruction::ARB(m) => vec![m],
+            Instruction::HLT => vec![],
+        }
+    }
+
+    /// Number of words taken up by the instruction and its parameters.
+    pub fn size(&self) -> usize {
+        self.modes().len() + 1
+    }
+
+    /// Whether the instruction writes to its last parameter.
+    pub fn writes(&self) -> bool {
+        matches!(
+            self,
+            Instruction::ADD(_)
+            | Instruction::MUL(_)
+            | Instruction::IN(_)
+            | Instruction::LT(_)
+            | Instruction::EQ(_)
+        )
+    }
+}
+
+impl From<ParameterMode> for i64 {
+    fn from(mode: ParameterMode) -> Self {
+        match mode {
+            ParameterMode::Position => 0,
+            ParameterMode::Immediate => 1,
+            ParameterMode::Relative => 2,
+        }
+    }
+}
+
+impl From<Instruction> for i64 {
+    fn from(instruction: Instruction) -> Self {
+        instruction.modes()
+            .into_iter()
+            .zip(&[100, 1000, 10000])
+            .fold(instruction.opcode(), |n, (mode, m)| n + i64::from(mode) * m)
+    }
+}
+
+impl TryFrom<i64> for ParameterMode {
+    type Error = ErrorKind;
+
+    fn try_from(n: i64) -> Result<Self, Self::Error> {
         match n {
-            0 => ParameterMode::Position,
-            1 => ParameterMode::Immediate,
-            2 => ParameterMode::Relative,
-            _ => panic!("Unknown parameter mode: {}", n),
+            0 => Ok(ParameterMode::Position),
+            1 => Ok(ParameterMode::Immediate),
+            2 => Ok(ParameterMode::Relative),
+            _ => Err(ErrorKind::UnknownParameterMode(n)),
         }
     }
 }
 
-impl From<i64> for Instruction {
-    fn from(n: i64) -> Self {
-        let param_mode_1 = ((n / 100) % 10).into();
-        let param_mode_2 = ((n / 1000) % 10).into();
-        let param_mode_3 = ((n / 10000) % 10).into();
+impl TryFrom<i64> for Instruction {
+    type Error = ErrorKind;
+
+    fn try_from(n: i64) -> Result<Self, Self::Error> {
+        let param_mode_1 = || ((n / 100) % 10).try_into();
+        let param_mode_2 = || ((n / 1000) % 10).try_into();
+        let param_mode_3 = || ((n / 10000) % 10).try_into();
 
         let opcode = n % 100;
-        match opcode {
-            1 => Instruction::ADD((param_mode_1, param_mode_2, param_mode_3)),
-            2 => Instruction::MUL((param_mode_1, param_mode_2, param_mode_3)),
-            3 => Instruction::IN(param_mode_1),
-            4 => Instruction::OUT(param_mode_1),
-            5 => Instruction::JNZ((param_mode_1, param_mode_2)),
-            6 => Instruction::JEZ((param_mode_1, param_mode_2)),
-            7 => Instruction::LT((param_mode_1, param_mode_2, param_mode_3)),
-            8 => Instruction::EQ((param_mode_1, param_mode_2, param_mode_3)),
-            9 => Instruction::ARB(param_mode_1),
+        let instruction = match opcode {
+            1 => Instruction::ADD((param_mode_1()?, param_mode_2()?, param_mode_3()?)),
+            2 => Instruction::MUL((param_mode_1()?, param_mode_2()?, param_mode_3()?)),
+            3 => Instruction::IN(param_mode_1()?),
+            4 => Instruction::OUT(param_mode_1()?),
+            5 => Instruction::JNZ((param_mode_1()?, param_mode_2()?)),
+            6 => Instruction::JEZ((param_mode_1()?, param_mode_2()?)),
+            7 => Instruction::LT((param_mode_1()?, param_mode_2()?, param_mode_3()?)),
+            8 => Instruction::EQ((param_mode_1()?, param_mode_2()?, param_mode_3()?)),
+            9 => Instruction::ARB(param_mode_1()?),
             99 => Instruction::HLT,
-            _ => panic!("Unknown opcode: {}", opcode),
-        }
+            _ => return Err(ErrorKind::UnknownOpcode(opcode)),
+        };
+
+        Ok(instruction)
     }
 }
\ No newline at end of file
@@ -1,4 +1,6 @@
 use std::{
+    collections::VecDeque,
+    convert::TryFrom,
     sync::mpsc::{channel, Receiver, Sender},
     io::{
         Result,
@@ -7,18 +9,20 @@ use std::{
     }
 };
 
-pub trait IO {
-    fn read(&mut self) -> Result<i64>;
-    fn write(&mut self, val: i64);
+pub trait IO<W = i64> {
+    fn read(&mut self) -> Result<W>;
+    fn write(&mut self, val: W);
 }
 
+#[derive(Clone)]
 pub struct NoIO;
 
-impl IO for NoIO {
-    fn read(&mut self) -> Result<i64> { Ok(0) }
-    fn write(&mut self, _: i64) {}
+impl<W: From<i64>> IO<W> for NoIO {
+    fn read(&mut self) -> Result<W> { Ok(W::from(0)) }
+    fn write(&mut self, _: W) {}
 }
 
+#[derive(Clone)]
 pub struct SingleIO {
     val: i64,
 }
@@ -32,6 +36,108 @@ impl IO for SingleIO {
     fn write(&mut self, val: i64) { self.val = val; }
 }
 
+/// Feeds queued inputs in order and records every output.
+#[derive(Clone, Default)]
+pub struct BufferedIO<W = i64> {
+    pub input: VecDeque<W>,
+    pub output: Vec<W>,
+}
+
+impl<W> BufferedIO<W> {
+    pub fn new<I: IntoIterator<Item = W>>(input: I) -> Self {
+        Self {
+            input: input.into_iter().collect(),
+            output: vec![],
+        }
+    }
+}
+
+impl<W> IO<W> for BufferedIO<W> {
+    fn read(&mut self) -> Result<W> {
+        self.input.pop_front()
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "input queue is empty"))
+    }
+
+    fn write(&mut self, val: W) {
+        self.output.push(val);
+    }
+}
+
+/// Calls `read` for every input and `write` for every output. Returning
+/// `None` from `read` ends the input.
+pub struct FnIO<R, W>
+    where R: FnMut() -> Option<i64>, W: FnMut(i64) {
+    read: R,
+    write: W,
+}
+
+impl<R, W> FnIO<R, W>
+    where R: FnMut() -> Option<i64>, W: FnMut(i64) {
+    pub fn new(read: R, write: W) -> Self {
+        Self { read, write }
+    }
+}
+
+impl<R, W> IO for FnIO<R, W>
+    where R: FnMut() -> Option<i64>, W: FnMut(i64) {
+    fn read(&mut self) -> Result<i64> {
+        (self.read)()
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "read callback returned no input"))
+    }
+
+    fn write(&mut self, val: i64) {
+        (self.write)(val);
+    }
+}
+
+/// Speaks ASCII: input is queued a line at a time and output is collected
+/// as text. Output values outside the ASCII range are kept separately as
+/// numeric results.
+#[derive(Clone, Default)]
+pub struct AsciiIO {
+    input: VecDeque<i64>,
+    text: String,
+    results: Vec<i64>,
+}
+
+impl AsciiIO {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `line` followed by a newline.
+    pub fn push_line(&mut self, line: &str) {
+        self.input.extend(line.bytes().map(i64::from));
+        self.input.push_back(i64::from(b'\n'));
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+
+    pub fn results(&self) -> &[i64] {
+        &self.results
+    }
+}
+
+impl IO for AsciiIO {
+    fn read(&mut self) -> Result<i64> {
+        self.input.pop_front()
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "no more input lines"))
+    }
+
+    fn write(&mut self, val: i64) {
+        match u8::try_from(val) {
+            Ok(b) if b.is_ascii() => self.text.push(char::from(b)),
+            _ => self.results.push(val),
+        }
+    }
+}
+
 pub struct AsyncIO {
     tx: Sender<i64>,
     rx: Receiver<i64>,
@@ -82,4 +188,51 @@ impl Pipe {
             }
         }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffered_io() {
+        let mut io = BufferedIO::new(vec![1, 2]);
+        io.write(3);
+        io.write(4);
+
+        assert_eq!(io.read().unwrap(), 1);
+        assert_eq!(io.read().unwrap(), 2);
+        assert!(io.read().is_err());
+        assert_eq!(io.output, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_fn_io() {
+        let mut next = 0;
+        let mut sum = 0;
+        {
+            let mut io = FnIO::new(|| { next += 1; Some(next) }, |val| sum += val);
+            assert_eq!(io.read().unwrap(), 1);
+            assert_eq!(io.read().unwrap(), 2);
+            io.write(5);
+            io.write(6);
+        }
+        assert_eq!(sum, 11);
+    }
+
+    #[test]
+    fn test_ascii_io() {
+        let mut io = AsciiIO::new();
+        io.push_line("A,B");
+
+        let input = std::iter::from_fn(|| io.read().ok()).collect::<Vec<_>>();
+        assert_eq!(input, vec![65, 44, 66, 10]);
+
+        for val in [104, 105, 10, 33, 1234567, -1] {
+            io.write(val);
+        }
+        assert_eq!(io.text(), "hi\n!");
+        assert_eq!(io.lines().collect::<Vec<_>>(), vec!["hi", "!"]);
+        assert_eq!(io.results(), &[1234567, -1]);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,167 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::intcode_computer::Word;
+
+/// Backing store for an `IntcodeComputer`. Addresses that were never
+/// written read as zero.
+pub trait Memory: Clone {
+    type Word: Word;
+
+    fn from_slice(program: &[Self::Word]) -> Self;
+
+    /// Loads a program, widening every value to the memory's word type.
+    fn from_program(program: &[i64]) -> Self {
+        Self::from_slice(&program.iter().map(|&val| Self::Word::from(val)).collect::<Vec<_>>())
+    }
+
+    fn read(&self, addr: usize) -> Self::Word;
+
+    fn write(&mut self, addr: usize, val: Self::Word);
+
+    /// One past the highest address that was loaded or written.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Address ranges actually held, in ascending order. Addresses outside
+    /// of them read as zero.
+    fn ranges(&self) -> Vec<Range<usize>> {
+        std::iter::once(0..self.len()).collect()
+    }
+
+    fn to_vec(&self) -> Vec<Self::Word> {
+        (0..self.len()).map(|addr| self.read(addr)).collect()
+    }
+}
+
+/// A single `Vec` that grows up to the highest address written.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct DenseMemory<W = i64>(Vec<W>);
+
+impl<W: Word> DenseMemory<W> {
+    pub fn as_slice(&self) -> &[W] {
+        &self.0
+    }
+}
+
+impl<W: Word> Memory for DenseMemory<W> {
+    type Word = W;
+
+    fn from_slice(program: &[W]) -> Self {
+        DenseMemory(program.to_vec())
+    }
+
+    fn read(&self, addr: usize) -> W {
+        self.0.get(addr).cloned().unwrap_or_else(|| W::from(0))
+    }
+
+    fn write(&mut self, addr: usize, val: W) {
+        if addr >= self.0.len() {
+            self.0.resize(addr + 1, W::from(0));
+        }
+
+        self.0[addr] = val;
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn to_vec(&self) -> Vec<W> {
+        self.0.clone()
+    }
+}
+
+const PAGE_SIZE: usize = 1024;
+
+/// Fixed-size pages allocated on first write, so far apart addresses only
+/// cost a page each.
+#[derive(Debug, Clone)]
+pub struct PagedMemory<W = i64> {
+    pages: HashMap<usize, Box<[W]>>,
+    len: usize,
+}
+
+impl<W> Default for PagedMemory<W> {
+    fn default() -> Self {
+        Self {
+            pages: HashMap::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<W> PagedMemory<W> {
+    pub fn pages(&self) -> usize {
+        self.pages.len()
+    }
+}
+
+impl<W: Word> Memory for PagedMemory<W> {
+    type Word = W;
+
+    fn from_slice(program: &[W]) -> Self {
+        let mut memory = PagedMemory::default();
+        for (addr, val) in program.iter().enumerate() {
+            memory.write(addr, val.clone());
+        }
+        memory
+    }
+
+    fn read(&self, addr: usize) -> W {
+        self.pages
+            .get(&(addr / PAGE_SIZE))
+            .map_or_else(|| W::from(0), |page| page[addr % PAGE_SIZE].clone())
+    }
+
+    fn write(&mut self, addr: usize, val: W) {
+        let page = self.pages
+            .entry(addr / PAGE_SIZE)
+            .or_insert_with(|| vec![W::from(0); PAGE_SIZE].into_boxed_slice());
+
+        page[addr % PAGE_SIZE] = val;
+        self.len = self.len.max(addr + 1);
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Runs of adjacent allocated pages, the last one ending at `len`.
+    fn ranges(&self) -> Vec<Range<usize>> {
+        let mut pages = self.pages.keys().copied().collect::<Vec<_>>();
+        pages.sort_unstable();
+
+        let mut ranges: Vec<Range<usize>> = vec![];
+        for page in pages {
+            let start = page * PAGE_SIZE;
+            let end = (start + PAGE_SIZE).min(self.len);
+
+            match ranges.last_mut() {
+                Some(range) if range.end == start => range.end = end,
+                _ => ranges.push(start..end),
+            }
+        }
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paged_memory() {
+        let mut memory = PagedMemory::from_program(&[1, 2, 3]);
+        memory.write(1 << 40, 7);
+
+        assert_eq!(memory.read(2), 3);
+        assert_eq!(memory.read(1 << 40), 7);
+        assert_eq!(memory.read(5000), 0);
+        assert_eq!(memory.pages(), 2);
+        assert_eq!(memory.len(), (1 << 40) + 1);
+        assert_eq!(memory.ranges(), vec![0..PAGE_SIZE, 1 << 40..(1 << 40) + 1]);
+    }
+}
@@ -1,10 +1,38 @@
-use std::io::{BufReader, Read, BufRead};
-
-use crate::intcode_computer::instructions::*;
+use std::{
+    convert::TryFrom,
+    io::{BufReader, Read, BufRead},
+    str::FromStr,
+    sync::Arc,
+    time::Instant,
+};
+
+pub use crate::intcode_computer::assembler::*;
+pub use crate::intcode_computer::control_flow::*;
+pub use crate::intcode_computer::disassembler::*;
+pub use crate::intcode_computer::error::*;
+pub use crate::intcode_computer::instructions::*;
 pub use crate::intcode_computer::io::*;
-
+pub use crate::intcode_computer::memory::*;
+pub use crate::intcode_computer::replay::*;
+pub use crate::intcode_computer::snapshot::*;
+pub use crate::intcode_computer::word::*;
+
+mod assembler;
+mod control_flow;
+pub mod coverage;
+pub mod debugger;
+mod disassembler;
+mod error;
+pub mod fuzz;
 mod instructions;
 pub mod io;
+mod memory;
+pub mod network;
+pub mod nic;
+mod replay;
+mod snapshot;
+pub mod trace;
+mod word;
 
 pub fn read_program<R: Read>(r: R) -> Vec<i64> {
     BufReader::new(r)
@@ -15,199 +43,597 @@ pub fn read_program<R: Read>(r: R) -> Vec<i64> {
         .collect()
 }
 
-pub struct IntcodeComputer<T>
-    where T: IO {
+// shared by the text formats of snapshots and transcripts
+fn parse_value<T: FromStr>(s: &str) -> std::io::Result<T> {
+    s.trim().parse().map_err(|_| invalid_data(format!("invalid value {:?}", s)))
+}
+
+fn invalid_data(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Event<W = i64> {
+    NeedInput,
+    Output(W),
+    Halted,
+}
+
+/// Why `run` returned. A machine blocked on input has its pc on the IN
+/// instruction and continues once more input is available.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Status {
+    Halted,
+    BlockedOnInput,
+}
+
+/// Memory addresses an instruction reads from and writes to, not counting
+/// the instruction and parameter words themselves.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Access {
+    pub reads: Vec<usize>,
+    pub write: Option<usize>,
+}
+
+/// How instructions are fetched. `Cached` decodes every word of the first
+/// memory range, which holds the program, up front and marks an entry stale
+/// once any of its words is written. Code outside of it is decoded as it
+/// runs. Clones share the decoded table, so forking a prepared machine is
+/// cheap.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Engine {
+    Interpreter,
+    Cached,
+}
+
+#[derive(Debug, Clone)]
+struct Decoded<W> {
+    instruction: Instruction,
+    params: [W; 3],
+}
+
+#[derive(Clone)]
+struct DecodeCache<W> {
+    decoded: Arc<Vec<Option<Decoded<W>>>>,
+    // one bit per address
+    stale: Vec<u64>,
+}
+
+impl<W: Clone> DecodeCache<W> {
+    fn get(&self, pc: usize) -> Option<Decoded<W>> {
+        match self.stale.get(pc / 64) {
+            Some(bits) if bits & (1 << (pc % 64)) == 0 => self.decoded.get(pc).cloned().flatten(),
+            _ => None,
+        }
+    }
+
+    fn invalidate(&mut self, addr: usize) {
+        let start = addr.saturating_sub(MAX_INSTRUCTION_SIZE - 1);
+        for a in start..(addr + 1).min(self.decoded.len()) {
+            self.stale[a / 64] |= 1 << (a % 64);
+        }
+    }
+}
+
+const MAX_INSTRUCTION_SIZE: usize = 4;
+
+// reading the clock on every instruction would dominate short instructions
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+
+/// Converts a word used as an address. Values beyond `i64` don't fit any
+/// address.
+fn address<W: Word>(word: &W) -> Result<usize, ErrorKind> {
+    match word.to_i64() {
+        Some(addr) if addr < 0 => Err(ErrorKind::NegativeAddress(addr)),
+        Some(addr) => Ok(addr as usize),
+        None => Err(ErrorKind::Overflow),
+    }
+}
+
+#[derive(Clone)]
+pub struct IntcodeComputer<T, M = DenseMemory>
+    where T: IO<M::Word>, M: Memory {
     pub io: T,
     halted: bool,
-    memory: Vec<i64>,
+    input: Option<M::Word>,
+    memory: M,
+    max_address: Option<usize>,
+    max_steps: Option<u64>,
+    deadline: Option<Instant>,
+    steps: u64,
     pc: usize,
-    rel_base: i64,
+    rel_base: M::Word,
+    cache: Option<DecodeCache<M::Word>>,
+    transcript: Option<Transcript<M::Word>>,
 }
 
 impl<T> IntcodeComputer<T>
     where T: IO {
-    pub fn new(memory: &mut Vec<i64>, io: T) -> IntcodeComputer<T> {
+    pub fn new(memory: &[i64], io: T) -> IntcodeComputer<T> {
+        IntcodeComputer::with_memory(DenseMemory::from_slice(memory), io)
+    }
+
+    pub fn from_snapshot(snapshot: &Snapshot, io: T) -> IntcodeComputer<T> {
+        let mut computer = IntcodeComputer::new(&[], io);
+        computer.restore(snapshot);
+        computer
+    }
+}
+
+impl<T, M> IntcodeComputer<T, M>
+    where T: IO<M::Word>, M: Memory {
+    pub fn with_memory(memory: M, io: T) -> IntcodeComputer<T, M> {
         IntcodeComputer {
             io,
             halted: false,
-            memory: memory.clone(),
+            input: None,
+            memory,
+            max_address: None,
+            max_steps: None,
+            deadline: None,
+            steps: 0,
             pc: 0,
-            rel_base: 0,
+            rel_base: M::Word::from(0),
+            cache: None,
+            transcript: None,
         }
     }
 
-    pub fn run(&mut self) {
-        while !self.halted {
-            let instruction = self.get_instruction();
+    /// Makes writes above `max_address` fail with
+    /// `ErrorKind::AddressOutOfRange` instead of growing memory.
+    pub fn with_max_address(mut self, max_address: usize) -> Self {
+        self.max_address = Some(max_address);
+        self
+    }
 
-            match instruction {
-                Instruction::ADD(modes) => {
-                    self.add(modes);
-                    self.inc_pc(4);
-                }
-                Instruction::MUL(modes) => {
-                    self.multiply(modes);
-                    self.inc_pc(4);
-                }
-                Instruction::IN(mode) => {
-                    self.input(mode);
-                    self.inc_pc(2);
-                }
-                Instruction::OUT(mode) => {
-                    self.output(mode);
-                    self.inc_pc(2);
-                }
-                Instruction::JNZ(modes) => {
-                    self.jump_not_zero(modes);
-                }
-                Instruction::JEZ(modes) => {
-                    self.jump_equal_zero(modes);
+    /// Stops execution with `ErrorKind::StepLimitExceeded` once `max_steps`
+    /// instructions have been executed. The machine is left as it was before
+    /// the next instruction, so it can be inspected or resumed after raising
+    /// the limit.
+    pub fn with_step_limit(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Stops execution with `ErrorKind::DeadlineExceeded` once `deadline`
+    /// has passed. The clock is only checked every 1024 instructions.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.cache = match engine {
+            Engine::Interpreter => None,
+            Engine::Cached => Some(self.build_cache()),
+        };
+        self
+    }
+
+    /// Records every value consumed by IN and produced by OUT, whichever
+    /// way the machine is driven.
+    pub fn with_recording(mut self) -> Self {
+        self.transcript = Some(Transcript::default());
+        self
+    }
+
+    fn build_cache(&self) -> DecodeCache<M::Word> {
+        let len = self.memory.ranges()
+            .first()
+            .filter(|range| range.start == 0)
+            .map_or(0, |range| range.end);
+        let decoded = (0..len)
+            .map(|addr| self.decode(addr).ok())
+            .collect();
+
+        DecodeCache {
+            decoded: Arc::new(decoded),
+            stale: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot<M::Word> {
+        let memory = self.memory.ranges()
+            .into_iter()
+            .map(|range| (range.start, range.map(|addr| self.read(addr)).collect()))
+            .collect();
+
+        Snapshot {
+            memory,
+            pc: self.pc,
+            rel_base: self.rel_base.clone(),
+            halted: self.halted,
+            input: self.input.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, snapshot: &Snapshot<M::Word>) {
+        self.memory = M::from_slice(&[]);
+        for (start, values) in &snapshot.memory {
+            for (i, val) in values.iter().enumerate() {
+                self.memory.write(start + i, val.clone());
+            }
+        }
+
+        self.pc = snapshot.pc;
+        self.rel_base = snapshot.rel_base.clone();
+        self.halted = snapshot.halted;
+        self.input = snapshot.input.clone();
+
+        if self.cache.is_some() {
+            self.cache = Some(self.build_cache());
+        }
+    }
+
+    /// Runs until the program halts or `io` runs out of input, feeding IN
+    /// and OUT through `io`.
+    pub fn run(&mut self) -> Result<Status, IntcodeError> {
+        loop {
+            match self.run_until_event()? {
+                Event::NeedInput => {
+                    if !self.read_io() {
+                        return Ok(Status::BlockedOnInput);
+                    }
                 }
-                Instruction::LT(modes) => {
-                    self.less_than(modes);
-                    self.inc_pc(4);
+                Event::Output(val) => self.io.write(val),
+                Event::Halted => return Ok(Status::Halted),
+            }
+        }
+    }
+
+    /// Executes exactly one instruction, feeding IN and OUT through `io` like
+    /// `run` does. Returns `Event::NeedInput` without executing anything if
+    /// `io` has no input.
+    pub fn step_io(&mut self) -> Result<Option<Event<M::Word>>, IntcodeError> {
+        match self.step()? {
+            Some(Event::NeedInput) => {
+                if self.read_io() {
+                    self.step()
                 }
-                Instruction::EQ(modes) => {
-                    self.equals(modes);
-                    self.inc_pc(4);
+                else {
+                    Ok(Some(Event::NeedInput))
                 }
-                Instruction::ARB(mode) => {
-                    self.adjust_rel_base(mode);
-                    self.inc_pc(2);
+            }
+            Some(Event::Output(val)) => {
+                self.io.write(val.clone());
+                Ok(Some(Event::Output(val)))
+            }
+            event => Ok(event),
+        }
+    }
+
+    /// Runs until the program needs input, produces output or halts,
+    /// without touching `io`. Input is supplied with `provide_input`.
+    pub fn run_until_event(&mut self) -> Result<Event<M::Word>, IntcodeError> {
+        loop {
+            if let Some(event) = self.step()? {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Executes exactly one instruction. An IN without pending input leaves
+    /// the pc on the instruction and returns `Event::NeedInput`.
+    pub fn step(&mut self) -> Result<Option<Event<M::Word>>, IntcodeError> {
+        if self.halted {
+            return Ok(Some(Event::Halted));
+        }
+
+        let pc = self.pc;
+
+        let event = self.check_budget()
+            .and_then(|_| self.fetch())
+            .and_then(|decoded| self.execute(decoded))
+            .map_err(|kind| self.error(pc, kind))?;
+
+        if event != Some(Event::NeedInput) {
+            self.steps += 1;
+        }
+
+        Ok(event)
+    }
+
+    fn error(&self, pc: usize, kind: ErrorKind) -> IntcodeError {
+        IntcodeError::new(pc, self.read(pc).saturating_i64(), kind)
+    }
+
+    fn check_budget(&self) -> Result<(), ErrorKind> {
+        if self.max_steps.is_some_and(|max| self.steps >= max) {
+            return Err(ErrorKind::StepLimitExceeded(self.steps));
+        }
+
+        match self.deadline {
+            Some(deadline) if self.steps.is_multiple_of(DEADLINE_CHECK_INTERVAL) && Instant::now() >= deadline => {
+                Err(ErrorKind::DeadlineExceeded)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Sets the value consumed by the next IN instruction.
+    pub fn provide_input(&mut self, val: M::Word) {
+        self.input = Some(val);
+    }
+
+    fn read_io(&mut self) -> bool {
+        match self.io.read() {
+            Ok(val) => {
+                self.provide_input(val);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn fetch(&self) -> Result<Decoded<M::Word>, ErrorKind> {
+        if let Some(decoded) = self.cache.as_ref().and_then(|cache| cache.get(self.pc)) {
+            return Ok(decoded);
+        }
+
+        self.decode(self.pc)
+    }
+
+    fn decode(&self, pc: usize) -> Result<Decoded<M::Word>, ErrorKind> {
+        let instruction = Instruction::try_from(self.read(pc).saturating_i64())?;
+        let params = std::array::from_fn(|i| {
+            if i + 1 < instruction.size() {
+                self.read(pc + i + 1)
+            }
+            else {
+                M::Word::from(0)
+            }
+        });
+
+        Ok(Decoded { instruction, params })
+    }
+
+    fn execute(&mut self, Decoded { instruction, params }: Decoded<M::Word>) -> Result<Option<Event<M::Word>>, ErrorKind> {
+        match instruction {
+            Instruction::ADD(modes) => {
+                self.add(modes, &params)?;
+                self.inc_pc(4);
+            }
+            Instruction::MUL(modes) => {
+                self.multiply(modes, &params)?;
+                self.inc_pc(4);
+            }
+            Instruction::IN(mode) => {
+                if !self.input(mode, &params[0])? {
+                    return Ok(Some(Event::NeedInput));
                 }
-                Instruction::HLT => self.halted = true,
+                self.inc_pc(2);
+            }
+            Instruction::OUT(mode) => {
+                let val = self.get_val(&params[0], mode)?;
+                self.record(Entry::Output { step: self.steps, value: val.clone() });
+                self.inc_pc(2);
+                return Ok(Some(Event::Output(val)));
+            }
+            Instruction::JNZ(modes) => {
+                self.jump_not_zero(modes, &params)?;
+            }
+            Instruction::JEZ(modes) => {
+                self.jump_equal_zero(modes, &params)?;
+            }
+            Instruction::LT(modes) => {
+                self.less_than(modes, &params)?;
+                self.inc_pc(4);
+            }
+            Instruction::EQ(modes) => {
+                self.equals(modes, &params)?;
+                self.inc_pc(4);
+            }
+            Instruction::ARB(mode) => {
+                self.adjust_rel_base(mode, &params[0])?;
+                self.inc_pc(2);
+            }
+            Instruction::HLT => {
+                self.halted = true;
+                return Ok(Some(Event::Halted));
             }
         }
+
+        Ok(None)
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
     }
 
-    pub fn read(&self, addr: usize) -> i64 {
-        if addr >= self.memory.len() {
-            return 0;
+    pub fn rel_base(&self) -> M::Word {
+        self.rel_base.clone()
+    }
+
+    pub fn halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Number of instructions executed so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    pub fn transcript(&self) -> Option<&Transcript<M::Word>> {
+        self.transcript.as_ref()
+    }
+
+    /// Takes the transcript recorded so far and keeps recording into a new
+    /// one.
+    pub fn take_transcript(&mut self) -> Option<Transcript<M::Word>> {
+        self.transcript.as_mut().map(std::mem::take)
+    }
+
+    pub fn memory(&self) -> &M {
+        &self.memory
+    }
+
+    /// Resolves the memory accesses of the instruction at the pc without
+    /// executing it.
+    pub fn next_access(&self) -> Result<Access, IntcodeError> {
+        let err = |kind| self.error(self.pc, kind);
+        let instruction = Instruction::try_from(self.read(self.pc).saturating_i64()).map_err(err)?;
+
+        let modes = instruction.modes();
+        let mut access = Access::default();
+
+        for (i, &mode) in modes.iter().enumerate() {
+            let param = self.read(self.pc + i + 1);
+
+            if instruction.writes() && i == modes.len() - 1 {
+                access.write = Some(self.get_dest(&param, mode).map_err(err)?);
+            }
+            else if mode != ParameterMode::Immediate {
+                access.reads.push(self.get_dest(&param, mode).map_err(err)?);
+            }
         }
 
-        self.memory[addr]
+        Ok(access)
     }
 
-    pub fn write(&mut self, addr: usize, val: i64) {
-        if addr >= self.memory.len() {
-            self.memory.resize(addr + 1, 0);
+    /// Decodes the instruction at the pc and resolves the values of its
+    /// source parameters without executing it.
+    pub fn next_operands(&self) -> Result<(Instruction, Vec<M::Word>), IntcodeError> {
+        let err = |kind| self.error(self.pc, kind);
+        let instruction = Instruction::try_from(self.read(self.pc).saturating_i64()).map_err(err)?;
+
+        let mut modes = instruction.modes();
+        if instruction.writes() {
+            modes.pop();
         }
 
-        self.memory[addr] = val;
+        let operands = modes
+            .into_iter()
+            .enumerate()
+            .map(|(i, mode)| self.get_val(&self.read(self.pc + i + 1), mode).map_err(err))
+            .collect::<Result<_, _>>()?;
+
+        Ok((instruction, operands))
     }
 
-    fn get_instruction(&self) -> Instruction {
-        self.read(self.pc).into()
+    pub fn read(&self, addr: usize) -> M::Word {
+        self.memory.read(addr)
+    }
+
+    pub fn write(&mut self, addr: usize, val: M::Word) -> Result<(), ErrorKind> {
+        if self.max_address.is_some_and(|max| addr > max) {
+            return Err(ErrorKind::AddressOutOfRange(addr));
+        }
+
+        self.memory.write(addr, val);
+
+        if let Some(cache) = &mut self.cache {
+            cache.invalidate(addr);
+        }
+
+        Ok(())
     }
 
     fn inc_pc(&mut self, amount: usize) {
         self.pc = self.pc.wrapping_add(amount);
     }
 
-    fn get_params_3(&self, (m1, m2, m3): (ParameterMode, ParameterMode, ParameterMode)) -> (i64, i64, usize) {
-        let param1 = self.read(self.pc + 1);
-        let param2 = self.read(self.pc + 2);
-        let param3 = self.read(self.pc + 3);
+    fn get_params_3(&self, (m1, m2, m3): (ParameterMode, ParameterMode, ParameterMode), [param1, param2, param3]: &[M::Word; 3]) -> Result<(M::Word, M::Word, usize), ErrorKind> {
+        let p1 = self.get_val(param1, m1)?;
+        let p2 = self.get_val(param2, m2)?;
+        let addr = self.get_dest(param3, m3)?;
 
-        let p1 = self.get_val(param1, m1);
-        let p2 = self.get_val(param2, m2);
-        let addr = self.get_dest(param3, m3);
-
-        (p1, p2, addr)
+        Ok((p1, p2, addr))
     }
 
-    fn get_params_2(&self, (m1, m2): (ParameterMode, ParameterMode)) -> (i64, i64) {
-        let param1 = self.read(self.pc + 1);
-        let param2 = self.read(self.pc + 2);
-
-        let p1 = self.get_val(param1, m1);
-        let p2 = self.get_val(param2, m2);
+    fn get_params_2(&self, (m1, m2): (ParameterMode, ParameterMode), [param1, param2, _]: &[M::Word; 3]) -> Result<(M::Word, M::Word), ErrorKind> {
+        let p1 = self.get_val(param1, m1)?;
+        let p2 = self.get_val(param2, m2)?;
 
-        (p1, p2)
+        Ok((p1, p2))
     }
 
-    fn get_val(&self, param: i64, mode: ParameterMode) -> i64 {
+    fn get_val(&self, param: &M::Word, mode: ParameterMode) -> Result<M::Word, ErrorKind> {
         match mode {
-            ParameterMode::Position => self.read(param as usize),
-            ParameterMode::Immediate => param,
-            ParameterMode::Relative => self.read((self.rel_base + param) as usize),
+            ParameterMode::Immediate => Ok(param.clone()),
+            _ => Ok(self.read(self.get_dest(param, mode)?)),
         }
     }
 
-    fn get_dest(&self, param: i64, mode: ParameterMode) -> usize {
+    fn get_dest(&self, param: &M::Word, mode: ParameterMode) -> Result<usize, ErrorKind> {
         match mode {
-            ParameterMode::Position => param as usize,
-            ParameterMode::Immediate => panic!("Not supported"),
-            ParameterMode::Relative => (self.rel_base + param) as usize,
+            ParameterMode::Position => address(param),
+            ParameterMode::Immediate => Err(ErrorKind::ImmediateDestination),
+            ParameterMode::Relative => address(&self.rel_base.checked_add(param).ok_or(ErrorKind::Overflow)?),
         }
     }
 
-    fn add(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode)) {
-        let (p1, p2, addr) = self.get_params_3(modes);
-        self.write(addr, p1 + p2);
+    fn add(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode), params: &[M::Word; 3]) -> Result<(), ErrorKind> {
+        let (p1, p2, addr) = self.get_params_3(modes, params)?;
+        self.write(addr, p1.checked_add(&p2).ok_or(ErrorKind::Overflow)?)
     }
 
-    fn multiply(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode)) {
-        let (p1, p2, addr) = self.get_params_3(modes);
-        self.write(addr, p1 * p2);
+    fn multiply(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode), params: &[M::Word; 3]) -> Result<(), ErrorKind> {
+        let (p1, p2, addr) = self.get_params_3(modes, params)?;
+        self.write(addr, p1.checked_mul(&p2).ok_or(ErrorKind::Overflow)?)
     }
 
-    fn input(&mut self,  mode: ParameterMode) {
-        let addr = self.get_dest(self.read(self.pc + 1), mode) as usize;
-        if let Ok(val) = self.io.read() {
-            self.write(addr, val);
+    fn input(&mut self, mode: ParameterMode, param: &M::Word) -> Result<bool, ErrorKind> {
+        let addr = self.get_dest(param, mode)?;
+        if let Some(val) = self.input.take() {
+            self.write(addr, val.clone())?;
+            self.record(Entry::Input { step: self.steps, value: val });
+            Ok(true)
         }
         else {
-            self.halted = true;
+            Ok(false)
         }
     }
 
-    fn output(&mut self, mode: ParameterMode) {
-        let val = self.get_val(self.read(self.pc + 1), mode);
-        self.io.write(val);
+    fn record(&mut self, entry: Entry<M::Word>) {
+        if let Some(transcript) = &mut self.transcript {
+            transcript.entries.push(entry);
+        }
     }
 
-    fn jump_not_zero(&mut self, modes: (ParameterMode, ParameterMode)) {
-        let (p1, addr) = self.get_params_2(modes);
-        if p1 != 0 {
-            self.pc = addr as usize;
+    fn jump_not_zero(&mut self, modes: (ParameterMode, ParameterMode), params: &[M::Word; 3]) -> Result<(), ErrorKind> {
+        let (p1, addr) = self.get_params_2(modes, params)?;
+        if p1 != M::Word::from(0) {
+            self.pc = address(&addr)?;
         }
         else {
             self.inc_pc(3);
         }
+        Ok(())
     }
 
-    fn jump_equal_zero(&mut self, modes: (ParameterMode, ParameterMode)) {
-        let (p1, addr) = self.get_params_2(modes);
-        if p1 == 0 {
-            self.pc = addr as usize;
+    fn jump_equal_zero(&mut self, modes: (ParameterMode, ParameterMode), params: &[M::Word; 3]) -> Result<(), ErrorKind> {
+        let (p1, addr) = self.get_params_2(modes, params)?;
+        if p1 == M::Word::from(0) {
+            self.pc = address(&addr)?;
         }
         else {
             self.inc_pc(3);
         }
+        Ok(())
     }
 
-    fn less_than(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode)) {
-        let (p1, p2, addr) = self.get_params_3(modes);
+    fn less_than(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode), params: &[M::Word; 3]) -> Result<(), ErrorKind> {
+        let (p1, p2, addr) = self.get_params_3(modes, params)?;
         let mut output = 0;
         if p1 < p2 {
             output = 1;
         }
-        self.write(addr, output);
+        self.write(addr, M::Word::from(output))
     }
 
-    fn equals(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode)) {
-        let (p1, p2, addr) = self.get_params_3(modes);
+    fn equals(&mut self, modes: (ParameterMode, ParameterMode, ParameterMode), params: &[M::Word; 3]) -> Result<(), ErrorKind> {
+        let (p1, p2, addr) = self.get_params_3(modes, params)?;
         let mut output = 0;
         if p1 == p2 {
             output = 1;
         }
-        self.write(addr, output);
+        self.write(addr, M::Word::from(output))
     }
 
-    fn adjust_rel_base(&mut self, mode: ParameterMode) {
-        let val = self.get_val(self.read(self.pc + 1 as usize), mode);
-        self.rel_base = self.rel_base + val;
+    fn adjust_rel_base(&mut self, mode: ParameterMode, param: &M::Word) -> Result<(), ErrorKind> {
+        let val = self.get_val(param, mode)?;
+        self.rel_base = self.rel_base.checked_add(&val).ok_or(ErrorKind::Overflow)?;
+        Ok(())
     }
 }
 
@@ -218,40 +644,40 @@ mod tests {
     #[test]
     fn test_decode_instruction() {
         assert_eq!(
-            Instruction::from(1002),
-            Instruction::MUL(
+            Instruction::try_from(1002),
+            Ok(Instruction::MUL(
                 (ParameterMode::Position,
                  ParameterMode::Immediate,
-                 ParameterMode::Position))
+                 ParameterMode::Position)))
         );
 
         assert_eq!(
-            Instruction::from(1108),
-            Instruction::EQ(
+            Instruction::try_from(1108),
+            Ok(Instruction::EQ(
                 (ParameterMode::Immediate,
                  ParameterMode::Immediate,
-                 ParameterMode::Position))
+                 ParameterMode::Position)))
         );
 
         assert_eq!(
-            Instruction::from(2002),
-            Instruction::MUL(
+            Instruction::try_from(2002),
+            Ok(Instruction::MUL(
                 (ParameterMode::Position,
                  ParameterMode::Relative,
                  ParameterMode::Position)
-            )
+            ))
         )
     }
 
-    fn test_program(mut program: Vec<i64>, expected_output: Vec<i64>) {
-        let mut computer = IntcodeComputer::new(&mut program, NoIO);
-        computer.run();
-        assert_eq!(computer.memory, expected_output);
+    fn test_program(program: Vec<i64>, expected_output: Vec<i64>) {
+        let mut computer = IntcodeComputer::new(&program, NoIO);
+        computer.run().unwrap();
+        assert_eq!(computer.memory.to_vec(), expected_output);
     }
 
-    fn test_program_output(mut program: Vec<i64>, input: i64, expected_output: i64) {
-        let mut computer = IntcodeComputer::new(&mut program, SingleIO::new(input));
-        computer.run();
+    fn test_program_output(program: Vec<i64>, input: i64, expected_output: i64) {
+        let mut computer = IntcodeComputer::new(&program, SingleIO::new(input));
+        computer.run().unwrap();
         assert_eq!(computer.io.read().unwrap(), expected_output);
     }
 
@@ -277,4 +703,239 @@ mod tests {
         test_program_output(program.clone(), 8, 1000);
         test_program_output(program.clone(), 9, 1001);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_unknown_opcode() {
+        let mut computer = IntcodeComputer::new(&[1101,1,2,5,42,0], NoIO);
+        let err = computer.run().unwrap_err();
+
+        assert_eq!(err.pc, 4);
+        assert_eq!(err.word, 42);
+        assert_eq!(err.kind, ErrorKind::UnknownOpcode(42));
+    }
+
+    #[test]
+    fn test_unknown_parameter_mode() {
+        let mut computer = IntcodeComputer::new(&[1301,1,2,5,99], NoIO);
+        let err = computer.run().unwrap_err();
+
+        assert_eq!(err.pc, 0);
+        assert_eq!(err.kind, ErrorKind::UnknownParameterMode(3));
+        assert_eq!(err.modes, [None, Some(ParameterMode::Immediate), Some(ParameterMode::Position)]);
+    }
+
+    #[test]
+    fn test_immediate_destination() {
+        let mut computer = IntcodeComputer::new(&[11101,1,2,5,99], NoIO);
+        let err = computer.run().unwrap_err();
+
+        assert_eq!(err.kind, ErrorKind::ImmediateDestination);
+        assert_eq!(err.modes, [Some(ParameterMode::Immediate); 3]);
+    }
+
+    #[test]
+    fn test_negative_address() {
+        let mut computer = IntcodeComputer::new(&[1101,1,1,-1,99], NoIO);
+        assert_eq!(computer.run(), Err(IntcodeError::new(0, 1101, ErrorKind::NegativeAddress(-1))));
+
+        let mut computer = IntcodeComputer::new(&[109,-3,204,1,99], NoIO);
+        assert_eq!(computer.run().unwrap_err().kind, ErrorKind::NegativeAddress(-2));
+
+        let mut computer = IntcodeComputer::new(&[1105,1,-7], NoIO);
+        assert_eq!(computer.run().unwrap_err().kind, ErrorKind::NegativeAddress(-7));
+    }
+
+    #[test]
+    fn test_run_until_event() {
+        // echoes inputs until it reads a zero
+        let program = vec![3,9,4,9,1005,9,0,99,0,0];
+        let mut computer = IntcodeComputer::new(&program, NoIO);
+
+        assert_eq!(computer.run_until_event().unwrap(), Event::NeedInput);
+        assert_eq!(computer.run_until_event().unwrap(), Event::NeedInput);
+
+        computer.provide_input(5);
+        assert_eq!(computer.run_until_event().unwrap(), Event::Output(5));
+        assert_eq!(computer.run_until_event().unwrap(), Event::NeedInput);
+
+        computer.provide_input(0);
+        assert_eq!(computer.run_until_event().unwrap(), Event::Output(0));
+        assert_eq!(computer.run_until_event().unwrap(), Event::Halted);
+        assert!(computer.halted());
+        assert_eq!(computer.run_until_event().unwrap(), Event::Halted);
+    }
+
+    #[test]
+    fn test_step() {
+        let mut computer = IntcodeComputer::new(&[1,1,1,4,99,5,6,0,99], NoIO);
+
+        assert_eq!(computer.step().unwrap(), None);
+        assert_eq!(computer.pc, 4);
+        assert_eq!(computer.read(4), 2);
+
+        assert_eq!(computer.step().unwrap(), None);
+        assert_eq!(computer.pc, 8);
+
+        assert_eq!(computer.step().unwrap(), Some(Event::Halted));
+        assert_eq!(computer.read(0), 30);
+    }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let program = vec![3,9,4,9,1005,9,0,99,0,0];
+        let mut computer = IntcodeComputer::new(&program, NoIO);
+
+        computer.provide_input(7);
+        assert_eq!(computer.run_until_event().unwrap(), Event::Output(7));
+        let snapshot = computer.snapshot();
+
+        assert_eq!(computer.run_until_event().unwrap(), Event::NeedInput);
+        computer.provide_input(0);
+        assert_eq!(computer.run_until_event().unwrap(), Event::Output(0));
+
+        computer.restore(&snapshot);
+        assert_eq!(computer.read(9), 7);
+        assert_eq!(computer.run_until_event().unwrap(), Event::NeedInput);
+
+        let mut fork = computer.clone();
+        fork.provide_input(3);
+        assert_eq!(fork.run_until_event().unwrap(), Event::Output(3));
+        assert_eq!(IntcodeComputer::from_snapshot(&snapshot, NoIO).snapshot(), snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_serialization() {
+        let mut computer = IntcodeComputer::new(&[109,19,204,-34,99], NoIO);
+        computer.step().unwrap();
+        computer.provide_input(-4);
+
+        let snapshot = computer.snapshot();
+        let mut buf = vec![];
+        snapshot.write_to(&mut buf).unwrap();
+
+        assert_eq!(Snapshot::read_from(&buf[..]).unwrap(), snapshot);
+        assert!(Snapshot::<i64>::read_from(&b"pc 0\nrel_base x\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_next_access() {
+        let mut computer = IntcodeComputer::new(&[109,5,21201,3,7,-2,99], NoIO);
+
+        assert_eq!(computer.next_access().unwrap(), Access::default());
+        computer.step().unwrap();
+
+        assert_eq!(computer.next_access().unwrap(), Access { reads: vec![8], write: Some(3) });
+        assert_eq!(computer.pc(), 2);
+        assert_eq!(computer.rel_base(), 5);
+    }
+
+    #[test]
+    fn test_memory_backends() {
+        let program = assemble("
+            ARB #1000000000000
+            ADD #1, #2, rb
+            OUT rb
+            HLT
+        ").unwrap();
+
+        let mut computer = IntcodeComputer::with_memory(PagedMemory::from_slice(&program), NoIO);
+        assert_eq!(computer.run_until_event().unwrap(), Event::Output(3));
+        assert_eq!(computer.memory().pages(), 2);
+
+        // neither may expand the far page into dense memory
+        let snapshot = computer.snapshot();
+        assert_eq!(snapshot.memory[1], (1_000_000_000_000, vec![3]));
+        let mut computer = computer.with_engine(Engine::Cached);
+        computer.restore(&snapshot);
+        assert_eq!(computer.snapshot(), snapshot);
+
+        let mut computer = IntcodeComputer::new(&program, NoIO).with_max_address(1 << 20);
+        assert_eq!(
+            computer.run_until_event(),
+            Err(IntcodeError::new(2, 21101, ErrorKind::AddressOutOfRange(1_000_000_000_000)))
+        );
+        assert_eq!(computer.write(1 << 20, 5), Ok(()));
+    }
+
+    #[test]
+    fn test_blocked_on_input() {
+        let program = assemble("
+            loop: IN  [x]
+                  OUT [x]
+                  JNZ [x], #loop
+                  HLT
+            x:    db  0
+        ").unwrap();
+
+        let mut computer = IntcodeComputer::new(&program, BufferedIO::new(vec![4]));
+        assert_eq!(computer.run().unwrap(), Status::BlockedOnInput);
+        assert_eq!(computer.pc(), 0);
+        assert!(!computer.halted());
+        assert_eq!(computer.step_io().unwrap(), Some(Event::NeedInput));
+
+        computer.io.input.extend(vec![5, 0]);
+        assert_eq!(computer.run().unwrap(), Status::Halted);
+        assert_eq!(computer.io.output, vec![4, 5, 0]);
+    }
+
+    #[test]
+    fn test_word_types() {
+        let program = assemble("
+            MUL #10000000000, #10000000000, [x]
+            OUT [x]
+            HLT
+            x: db 0
+        ").unwrap();
+
+        let mut computer = IntcodeComputer::new(&program, NoIO);
+        assert_eq!(computer.run_until_event().unwrap_err().kind, ErrorKind::Overflow);
+
+        let memory = DenseMemory::<num::BigInt>::from_program(&program);
+        let mut computer = IntcodeComputer::with_memory(memory, BufferedIO::new(vec![]));
+        assert_eq!(computer.run().unwrap(), Status::Halted);
+        assert_eq!(computer.io.output, vec!["100000000000000000000".parse().unwrap()]);
+    }
+
+    #[test]
+    fn test_budget() {
+        let program = assemble("loop: JEZ #0, #loop").unwrap();
+
+        let mut computer = IntcodeComputer::new(&program, NoIO).with_step_limit(10);
+        assert_eq!(
+            computer.run(),
+            Err(IntcodeError::new(0, 1106, ErrorKind::StepLimitExceeded(10)))
+        );
+        assert_eq!(computer.steps(), 10);
+
+        let mut computer = IntcodeComputer::new(&program, NoIO).with_deadline(Instant::now());
+        assert_eq!(computer.run().unwrap_err().kind, ErrorKind::DeadlineExceeded);
+        assert_eq!(computer.steps(), 0);
+    }
+
+    #[test]
+    fn test_cached_engine() {
+        // patches the parameter of the OUT at address 4 before running it
+        let program = [1101,1,1,5,104,0,99];
+        let template = IntcodeComputer::new(&program, NoIO).with_engine(Engine::Cached);
+
+        let mut computer = template.clone();
+        assert_eq!(computer.run_until_event().unwrap(), Event::Output(2));
+        assert_eq!(computer.run_until_event().unwrap(), Event::Halted);
+
+        let mut computer = template.clone();
+        computer.write(1, 41).unwrap();
+        assert_eq!(computer.run_until_event().unwrap(), Event::Output(42));
+
+        let day9 = read_program(std::fs::File::open("input/day09").unwrap());
+        let outputs = |engine| {
+            let mut computer = IntcodeComputer::new(&day9, NoIO).with_engine(engine);
+            computer.provide_input(1);
+            let mut outputs = vec![];
+            while let Event::Output(val) = computer.run_until_event().unwrap() {
+                outputs.push(val);
+            }
+            outputs
+        };
+        assert_eq!(outputs(Engine::Cached), outputs(Engine::Interpreter));
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+use crate::intcode_computer::{Event, IntcodeComputer, IntcodeError, NoIO};
+
+pub type NodeId = usize;
+
+// instructions a node may execute before yielding to the next one
+pub(crate) const QUANTUM: usize = 1000;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Outcome {
+    /// Every node halted.
+    Halted,
+    /// The listed nodes are waiting for input nobody will send; the others
+    /// halted.
+    Deadlock(Vec<NodeId>),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NetworkRun {
+    pub outcome: Outcome,
+    pub last_halted: Option<NodeId>,
+    /// Everything each node wrote, indexed by node id.
+    pub emitted: Vec<Vec<i64>>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct NodeError {
+    pub node: NodeId,
+    pub error: IntcodeError,
+}
+
+impl Display for NodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "node {}: {}", self.node, self.error)
+    }
+}
+
+impl Error for NodeError {}
+
+/// A link or input referring to a node the network doesn't have.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TopologyError {
+    pub node: NodeId,
+}
+
+impl Display for TopologyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown node {}", self.node)
+    }
+}
+
+impl Error for TopologyError {}
+
+/// A machine of a `Topology`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct NodeSpec {
+    pub program: Vec<i64>,
+    /// Values queued as input before the network runs.
+    pub input: Vec<i64>,
+}
+
+/// Declarative description of a network. Node ids are positions in `nodes`
+/// and each link is a `(from, to)` pair of them.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Topology {
+    pub nodes: Vec<NodeSpec>,
+    pub links: Vec<(NodeId, NodeId)>,
+    /// Instructions each node may execute in total before the run fails
+    /// with `ErrorKind::StepLimitExceeded`.
+    pub max_steps: Option<u64>,
+}
+
+impl Topology {
+    /// Links from each node to the next one.
+    pub fn chain(nodes: &[NodeId]) -> Vec<(NodeId, NodeId)> {
+        nodes.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    }
+
+    /// Links from each node to the next one and from the last to the first.
+    pub fn ring(nodes: &[NodeId]) -> Vec<(NodeId, NodeId)> {
+        let mut links = Self::chain(nodes);
+        if let (Some(&first), Some(&last)) = (nodes.first(), nodes.last()) {
+            links.push((last, first));
+        }
+        links
+    }
+}
+
+struct Node {
+    computer: IntcodeComputer<NoIO>,
+    input: VecDeque<i64>,
+    targets: Vec<NodeId>,
+    emitted: Vec<i64>,
+}
+
+/// Intcode machines whose outputs are wired to other machines' inputs.
+///
+/// Every output of a node is copied to each of its targets, so fan-out and
+/// broadcast are several links from one node, and fan-in is several links
+/// into one. Nodes take turns on a single thread until all of them have
+/// halted or none can make progress.
+#[derive(Default)]
+pub struct Network {
+    nodes: Vec<Node>,
+    max_steps: Option<u64>,
+}
+
+impl Network {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_topology(topology: &Topology) -> Result<Self, TopologyError> {
+        let mut net = Network { nodes: vec![], max_steps: topology.max_steps };
+        for node in &topology.nodes {
+            net.add_node(&node.program, node.input.iter().copied());
+        }
+        for &(from, to) in &topology.links {
+            net.link(from, to)?;
+        }
+        Ok(net)
+    }
+
+    /// Adds a machine running `program` with `input` queued.
+    pub fn add_node<I: IntoIterator<Item = i64>>(&mut self, program: &[i64], input: I) -> NodeId {
+        let mut computer = IntcodeComputer::new(program, NoIO);
+        if let Some(max_steps) = self.max_steps {
+            computer = computer.with_step_limit(max_steps);
+        }
+
+        self.nodes.push(Node {
+            computer,
+            input: input.into_iter().collect(),
+            targets: vec![],
+            emitted: vec![],
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn link(&mut self, from: NodeId, to: NodeId) -> Result<(), TopologyError> {
+        self.node_mut(to)?;
+        self.node_mut(from)?.targets.push(to);
+        Ok(())
+    }
+
+    /// Queues `val` as input for `node`.
+    pub fn send(&mut self, node: NodeId, val: i64) -> Result<(), TopologyError> {
+        self.node_mut(node)?.input.push_back(val);
+        Ok(())
+    }
+
+    fn node_mut(&mut self, node: NodeId) -> Result<&mut Node, TopologyError> {
+        self.nodes.get_mut(node).ok_or(TopologyError { node })
+    }
+
+    /// Runs the nodes until all of them have halted or are waiting for
+    /// input. A node that keeps computing without reading or writing never
+    /// lets the run finish, unless the topology set `max_steps`.
+    pub fn run(&mut self) -> Result<NetworkRun, NodeError> {
+        let mut last_halted = None;
+
+        loop {
+            let mut progress = false;
+
+            for id in 0..self.nodes.len() {
+                if self.nodes[id].computer.halted() {
+                    continue;
+                }
+
+                let before = self.nodes[id].computer.steps();
+                if self.run_node(id)? == Some(Event::Halted) {
+                    last_halted = Some(id);
+                }
+                progress |= self.nodes[id].computer.steps() != before;
+            }
+
+            if !progress {
+                break;
+            }
+        }
+
+        let blocked = (0..self.nodes.len())
+            .filter(|&id| !self.nodes[id].computer.halted())
+            .collect::<Vec<_>>();
+
+        Ok(NetworkRun {
+            outcome: if blocked.is_empty() { Outcome::Halted } else { Outcome::Deadlock(blocked) },
+            last_halted,
+            emitted: self.nodes.iter().map(|node| node.emitted.clone()).collect(),
+        })
+    }
+
+    /// Runs one node for up to `QUANTUM` instructions, stopping early when
+    /// it halts or waits for input that hasn't arrived.
+    fn run_node(&mut self, id: NodeId) -> Result<Option<Event>, NodeError> {
+        for _ in 0..QUANTUM {
+            let node = &mut self.nodes[id];
+            let event = node.computer.step().map_err(|error| NodeError { node: id, error })?;
+
+            match event {
+                Some(Event::NeedInput) => match node.input.pop_front() {
+                    Some(val) => node.computer.provide_input(val),
+                    None => return Ok(event),
+                },
+                Some(Event::Output(val)) => {
+                    node.emitted.push(val);
+                    for target in node.targets.clone() {
+                        self.nodes[target].input.push_back(val);
+                    }
+                }
+                Some(Event::Halted) => return Ok(event),
+                None => {}
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::{assemble, ErrorKind};
+
+    #[test]
+    fn test_network() {
+        // adds its first input to every following one until it reads 0
+        let adder = assemble("
+                  IN  [n]
+            loop: IN  [x]
+                  JEZ [x], #end
+                  ADD [x], [n], [x]
+                  OUT [x]
+                  JNZ #1, #loop
+            end:  HLT
+            n:    db  0
+            x:    db  0
+        ").unwrap();
+
+        let (a, b, c) = (0, 1, 2);
+        let mut net = Network::from_topology(&Topology {
+            nodes: [1, 10, 100].iter().map(|&n| NodeSpec { program: adder.clone(), input: vec![n] }).collect(),
+            links: vec![(a, b), (a, c)],
+            max_steps: None,
+        }).unwrap();
+        net.send(a, 5).unwrap();
+
+        let run = net.run().unwrap();
+        assert_eq!(run.outcome, Outcome::Deadlock(vec![a, b, c]));
+        assert_eq!(run.emitted, vec![vec![6], vec![16], vec![106]]);
+
+        net.send(a, 0).unwrap();
+        net.send(b, 0).unwrap();
+        net.send(c, 0).unwrap();
+        let run = net.run().unwrap();
+        assert_eq!(run.outcome, Outcome::Halted);
+        assert_eq!(run.last_halted, Some(c));
+
+        assert_eq!(Topology::chain(&[a, b, c]), vec![(a, b), (b, c)]);
+        assert_eq!(Topology::ring(&[a, b, c]), vec![(a, b), (b, c), (c, a)]);
+
+        assert_eq!(net.send(3, 0), Err(TopologyError { node: 3 }));
+        assert_eq!(net.link(a, 3), Err(TopologyError { node: 3 }));
+        let topology = Topology { nodes: vec![NodeSpec::default()], links: vec![(1, 0)], max_steps: None };
+        assert_eq!(Network::from_topology(&topology).err(), Some(TopologyError { node: 1 }));
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let spin = assemble("loop: JNZ #1, #loop").unwrap();
+        let mut net = Network::from_topology(&Topology {
+            nodes: vec![NodeSpec { program: vec![99], input: vec![] }, NodeSpec { program: spin, input: vec![] }],
+            links: vec![],
+            max_steps: Some(5000),
+        }).unwrap();
+
+        let err = net.run().unwrap_err();
+        assert_eq!(err.node, 1);
+        assert_eq!(err.error.kind, ErrorKind::StepLimitExceeded(5000));
+    }
+}
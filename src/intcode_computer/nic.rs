@@ -0,0 +1,223 @@
+use std::{
+    collections::VecDeque,
+    convert::TryFrom,
+    io,
+};
+
+use crate::intcode_computer::{
+    network::{NodeError, NodeId, QUANTUM},
+    IntcodeComputer, IO,
+};
+
+// consecutive empty reads after which a machine counts as idle
+const IDLE_READS: u32 = 2;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Packet {
+    pub src: i64,
+    pub dest: i64,
+    pub x: i64,
+    pub y: i64,
+}
+
+/// Network interface of a single machine. The first read returns the
+/// machine's address, later reads return queued packet values or -1 when
+/// nothing is queued. Every three writes form an outgoing packet.
+pub struct NicIO {
+    address: i64,
+    booted: bool,
+    inbox: VecDeque<i64>,
+    outgoing: Vec<i64>,
+    sent: Vec<Packet>,
+    idle_reads: u32,
+}
+
+impl NicIO {
+    pub fn new(address: i64) -> Self {
+        Self {
+            address,
+            booted: false,
+            inbox: VecDeque::new(),
+            outgoing: vec![],
+            sent: vec![],
+            idle_reads: 0,
+        }
+    }
+
+    pub fn deliver(&mut self, packet: &Packet) {
+        self.inbox.push_back(packet.x);
+        self.inbox.push_back(packet.y);
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.inbox.is_empty() && self.idle_reads >= IDLE_READS
+    }
+}
+
+impl IO for NicIO {
+    fn read(&mut self) -> io::Result<i64> {
+        if !self.booted {
+            self.booted = true;
+            return Ok(self.address);
+        }
+
+        match self.inbox.pop_front() {
+            Some(val) => {
+                self.idle_reads = 0;
+                Ok(val)
+            }
+            None => {
+                self.idle_reads += 1;
+                Ok(-1)
+            }
+        }
+    }
+
+    fn write(&mut self, val: i64) {
+        self.idle_reads = 0;
+        self.outgoing.push(val);
+
+        if let [dest, x, y] = self.outgoing[..] {
+            self.sent.push(Packet { src: self.address, dest, x, y });
+            self.outgoing.clear();
+        }
+    }
+}
+
+/// Machines addressed `0..size` exchanging packets, plus a NAT listening on
+/// the `monitor` address. The NAT keeps the last packet sent to it and,
+/// whenever every machine is idle, sends it on to address 0.
+pub struct PacketNetwork {
+    machines: Vec<IntcodeComputer<NicIO>>,
+    monitor: i64,
+    nat: Option<Packet>,
+    log: Vec<Packet>,
+}
+
+impl PacketNetwork {
+    pub fn new(program: &[i64], size: usize, monitor: i64) -> Self {
+        Self {
+            machines: (0..size)
+                .map(|address| IntcodeComputer::new(program, NicIO::new(address as i64)))
+                .collect(),
+            monitor,
+            nat: None,
+            log: vec![],
+        }
+    }
+
+    /// Every packet routed so far, including the ones sent by the NAT.
+    pub fn log(&self) -> &[Packet] {
+        &self.log
+    }
+
+    /// The last packet the NAT received.
+    pub fn nat(&self) -> Option<Packet> {
+        self.nat
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.machines.iter().all(|m| m.halted() || m.io.is_idle())
+    }
+
+    /// Gives each machine one turn, routes the packets they sent and wakes
+    /// the network through the NAT if it went idle. Returns the packets
+    /// routed during the tick.
+    pub fn tick(&mut self) -> Result<Vec<Packet>, NodeError> {
+        let start = self.log.len();
+
+        for node in 0..self.machines.len() {
+            self.run_machine(node)?;
+
+            let sent = std::mem::take(&mut self.machines[node].io.sent);
+            for packet in sent {
+                self.route(packet);
+            }
+        }
+
+        if self.is_idle() {
+            if let Some(Packet { x, y, .. }) = self.nat {
+                self.route(Packet { src: self.monitor, dest: 0, x, y });
+            }
+        }
+
+        Ok(self.log[start..].to_vec())
+    }
+
+    /// Runs a machine until its quantum is used up, it halts or it finds
+    /// its queue empty.
+    fn run_machine(&mut self, node: NodeId) -> Result<(), NodeError> {
+        let machine = &mut self.machines[node];
+
+        for _ in 0..QUANTUM {
+            if machine.halted() {
+                break;
+            }
+
+            let idle_reads = machine.io.idle_reads;
+            machine.step_io().map_err(|error| NodeError { node, error })?;
+
+            if machine.io.idle_reads > idle_reads {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn route(&mut self, packet: Packet) {
+        self.log.push(packet);
+
+        if packet.dest == self.monitor {
+            self.nat = Some(packet);
+        }
+        else if let Some(machine) = usize::try_from(packet.dest).ok().and_then(|dest| self.machines.get_mut(dest)) {
+            machine.io.deliver(&packet);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::assemble;
+
+    #[test]
+    fn test_packet_network() {
+        // machine 0 sends the first packet, every machine passes what it
+        // gets on to the next address with x incremented
+        let program = assemble("
+                  IN  [a]
+                  JNZ [a], #loop
+                  OUT #1
+                  OUT #7
+                  OUT #8
+            loop: IN  [x]
+                  EQ  [x], #-1, [t]
+                  JNZ [t], #loop
+                  IN  [y]
+                  ADD [a], #1, [d]
+                  ADD [x], #1, [x]
+                  OUT [d]
+                  OUT [x]
+                  OUT [y]
+                  JNZ #1, #loop
+            a:    db  0
+            x:    db  0
+            y:    db  0
+            t:    db  0
+            d:    db  0
+        ").unwrap();
+
+        let mut net = PacketNetwork::new(&program, 3, 3);
+        while net.log().last().is_none_or(|p| p.src != 3) {
+            net.tick().unwrap();
+        }
+
+        let packet = |src, dest, x| Packet { src, dest, x, y: 8 };
+        assert_eq!(net.log(), &[packet(0, 1, 7), packet(1, 2, 8), packet(2, 3, 9), packet(3, 0, 9)]);
+        assert_eq!(net.nat(), Some(packet(2, 3, 9)));
+
+        assert_eq!(net.tick().unwrap(), vec![packet(0, 1, 10), packet(1, 2, 11), packet(2, 3, 12)]);
+    }
+}
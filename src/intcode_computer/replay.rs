@@ -0,0 +1,188 @@
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+    io::{self, BufRead, BufReader, Read, Write},
+};
+
+use crate::intcode_computer::{
+    invalid_data, parse_value, Event, IntcodeComputer, IntcodeError, Memory, Word, IO,
+};
+
+/// A value that went through IN or OUT. `step` is the number of
+/// instructions executed before that IN or OUT.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Entry<W = i64> {
+    Input { step: u64, value: W },
+    Output { step: u64, value: W },
+}
+
+/// IO of a run in the order it happened, as recorded by
+/// `IntcodeComputer::with_recording`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Transcript<W = i64> {
+    pub entries: Vec<Entry<W>>,
+}
+
+impl<W> Default for Transcript<W> {
+    fn default() -> Self {
+        Self { entries: vec![] }
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ReplayError<W = i64> {
+    Intcode(IntcodeError),
+    /// The run did something other than entry `index` of the transcript,
+    /// or kept producing output after its end.
+    Diverged {
+        index: usize,
+        expected: Option<Entry<W>>,
+        actual: Event<W>,
+        step: u64,
+    },
+}
+
+impl<W: Word> Transcript<W> {
+    /// Writes one `in step value` or `out step value` line per entry.
+    pub fn write_to<T: Write>(&self, mut w: T) -> io::Result<()> {
+        for entry in &self.entries {
+            match entry {
+                Entry::Input { step, value } => writeln!(w, "in {} {}", step, value)?,
+                Entry::Output { step, value } => writeln!(w, "out {} {}", step, value)?,
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(r: R) -> io::Result<Self> {
+        let mut entries = vec![];
+
+        for line in BufReader::new(r).lines() {
+            let line = line?;
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+
+            let entry = match fields[..] {
+                [] => continue,
+                ["in", step, value] => Entry::Input { step: parse_value(step)?, value: parse_value(value)? },
+                ["out", step, value] => Entry::Output { step: parse_value(step)?, value: parse_value(value)? },
+                _ => return Err(invalid_data(format!("invalid transcript entry {:?}", line))),
+            };
+            entries.push(entry);
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Runs `computer` feeding it the recorded input, and checks that it
+    /// reads and writes exactly what was recorded at the same instruction
+    /// counts. The replay ends when the program halts or asks for input
+    /// past the end of the transcript. `io` isn't used.
+    pub fn replay<T, M>(&self, computer: &mut IntcodeComputer<T, M>) -> Result<(), ReplayError<W>>
+        where T: IO<W>, M: Memory<Word = W> {
+        let mut entries = self.entries.iter().enumerate();
+
+        loop {
+            let event = computer.run_until_event().map_err(ReplayError::Intcode)?;
+            let step = match event {
+                Event::Output(_) => computer.steps() - 1,
+                _ => computer.steps(),
+            };
+            let next = entries.next();
+
+            match (&event, next) {
+                (Event::NeedInput, Some((_, Entry::Input { step: s, value }))) if *s == step => {
+                    computer.provide_input(value.clone());
+                }
+                (Event::Output(val), Some((_, Entry::Output { step: s, value }))) if *s == step && val == value => {}
+                (Event::NeedInput, None) | (Event::Halted, None) => return Ok(()),
+                _ => {
+                    return Err(ReplayError::Diverged {
+                        index: next.map_or(self.entries.len(), |(index, _)| index),
+                        expected: next.map(|(_, entry)| entry.clone()),
+                        actual: event,
+                        step,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<W: Display> Display for Entry<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Input { step, value } => write!(f, "input {} at step {}", value, step),
+            Entry::Output { step, value } => write!(f, "output {} at step {}", value, step),
+        }
+    }
+}
+
+impl<W: Display> Display for ReplayError<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Intcode(e) => write!(f, "{}", e),
+            ReplayError::Diverged { index, expected, actual, step } => {
+                write!(f, "diverged at entry {}: expected ", index)?;
+                match expected {
+                    Some(entry) => write!(f, "{}", entry)?,
+                    None => write!(f, "end of transcript")?,
+                }
+                match actual {
+                    Event::NeedInput => write!(f, ", got input request at step {}", step),
+                    Event::Output(val) => write!(f, ", got output {} at step {}", val, step),
+                    Event::Halted => write!(f, ", got halt at step {}", step),
+                }
+            }
+        }
+    }
+}
+
+impl<W: Debug + Display> Error for ReplayError<W> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::{assemble, BufferedIO, NoIO};
+
+    #[test]
+    fn test_record_and_replay() {
+        // echoes its input doubled until it reads 0
+        let program = assemble("
+            loop: IN  [x]
+                  JEZ [x], #end
+                  MUL [x], #2, [x]
+                  OUT [x]
+                  JNZ #1, #loop
+            end:  HLT
+            x:    db  0
+        ").unwrap();
+
+        let mut computer = IntcodeComputer::new(&program, BufferedIO::new(vec![3, 5, 0])).with_recording();
+        computer.run().unwrap();
+        let transcript = computer.take_transcript().unwrap();
+        assert_eq!(transcript.entries[..3], [
+            Entry::Input { step: 0, value: 3 },
+            Entry::Output { step: 3, value: 6 },
+            Entry::Input { step: 5, value: 5 },
+        ]);
+
+        let mut file = vec![];
+        transcript.write_to(&mut file).unwrap();
+        assert!(String::from_utf8_lossy(&file).starts_with("in 0 3\nout 3 6\nin 5 5\n"));
+        let transcript = Transcript::read_from(&file[..]).unwrap();
+
+        assert_eq!(transcript.replay(&mut IntcodeComputer::new(&program, NoIO)), Ok(()));
+
+        // a controller that triples instead
+        let mut patched = program.clone();
+        patched[7] = 3;
+        let err = transcript.replay(&mut IntcodeComputer::new(&patched, NoIO)).unwrap_err();
+        assert_eq!(err, ReplayError::Diverged {
+            index: 1,
+            expected: Some(Entry::Output { step: 3, value: 6 }),
+            actual: Event::Output(9),
+            step: 3,
+        });
+        assert_eq!(err.to_string(), "diverged at entry 1: expected output 6 at step 3, got output 9 at step 3");
+    }
+}
@@ -0,0 +1,80 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+use crate::intcode_computer::{invalid_data, parse_value, Word};
+
+/// Full machine state of an `IntcodeComputer`, excluding its IO.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Snapshot<W = i64> {
+    /// The ranges held by the memory as start address and values.
+    pub memory: Vec<(usize, Vec<W>)>,
+    pub pc: usize,
+    pub rel_base: W,
+    pub halted: bool,
+    pub input: Option<W>,
+}
+
+impl<W: Word> Snapshot<W> {
+    /// Writes the snapshot as `key value` lines, memory last with one
+    /// `memory start values` line per range.
+    pub fn write_to<T: Write>(&self, mut w: T) -> io::Result<()> {
+        writeln!(w, "pc {}", self.pc)?;
+        writeln!(w, "rel_base {}", self.rel_base)?;
+        writeln!(w, "halted {}", self.halted)?;
+        match &self.input {
+            Some(val) => writeln!(w, "input {}", val)?,
+            None => writeln!(w, "input -")?,
+        }
+
+        for (start, values) in &self.memory {
+            let values = values.iter().map(W::to_string).collect::<Vec<_>>();
+            writeln!(w, "memory {} {}", start, values.join(","))?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(r: R) -> io::Result<Self> {
+        let mut lines = BufReader::new(r).lines();
+        let mut field = |key: &str| -> io::Result<String> {
+            let line = lines.next()
+                .unwrap_or_else(|| Err(invalid_data(format!("missing field {}", key))))?;
+
+            match line.split_once(' ') {
+                Some((k, v)) if k == key => Ok(v.to_string()),
+                _ => Err(invalid_data(format!("expected field {}, got {:?}", key, line))),
+            }
+        };
+
+        let pc = parse_value(&field("pc")?)?;
+        let rel_base = parse_value(&field("rel_base")?)?;
+        let halted = parse_value(&field("halted")?)?;
+        let input = match field("input")?.as_str() {
+            "-" => None,
+            val => Some(parse_value(val)?),
+        };
+
+        let mut memory = vec![];
+        for line in lines {
+            let line = line?;
+            let range = match line.split_once(' ') {
+                Some(("memory", range)) => range,
+                _ => return Err(invalid_data(format!("expected field memory, got {:?}", line))),
+            };
+
+            let (start, values) = range.split_once(' ').unwrap_or((range, ""));
+            let values = values
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(parse_value)
+                .collect::<io::Result<_>>()?;
+            memory.push((parse_value(start)?, values));
+        }
+
+        Ok(Snapshot {
+            memory,
+            pc,
+            rel_base,
+            halted,
+            input,
+        })
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Display, Formatter},
+    io::{self, Write},
+};
+
+use crate::intcode_computer::{
+    Event, Instruction, IntcodeComputer, IntcodeError, Memory, ParameterMode, IO,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TraceRecord {
+    pub step: u64,
+    pub pc: usize,
+    pub instruction: Instruction,
+    pub operands: Vec<i64>,
+    /// Addresses read as data, not counting the instruction words.
+    pub reads: Vec<usize>,
+    pub write: Option<(usize, i64)>,
+    pub next_pc: usize,
+}
+
+pub trait TraceSink {
+    fn record(&mut self, record: &TraceRecord);
+}
+
+impl<S> TraceSink for &mut S
+    where S: TraceSink + ?Sized {
+    fn record(&mut self, record: &TraceRecord) {
+        (**self).record(record);
+    }
+}
+
+impl<A, B> TraceSink for (A, B)
+    where A: TraceSink, B: TraceSink {
+    fn record(&mut self, record: &TraceRecord) {
+        self.0.record(record);
+        self.1.record(record);
+    }
+}
+
+impl<T, M> IntcodeComputer<T, M>
+    where T: IO, M: Memory<Word = i64> {
+    /// Same as `run`, but reports every executed instruction to `sink`.
+    /// Stops without an error when `io` runs out of input.
+    pub fn run_traced<S: TraceSink>(&mut self, sink: &mut S) -> Result<(), IntcodeError> {
+        let mut step = 0;
+
+        while !self.halted() {
+            let pc = self.pc();
+            let (instruction, operands) = self.next_operands()?;
+            let access = self.next_access()?;
+
+            if self.step_io()? == Some(Event::NeedInput) {
+                // input ran dry, nothing was executed
+                break;
+            }
+
+            sink.record(&TraceRecord {
+                step,
+                pc,
+                instruction,
+                operands,
+                reads: access.reads,
+                write: access.write.map(|addr| (addr, self.read(addr))),
+                next_pc: self.pc(),
+            });
+            step += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// Keeps the last `capacity` records.
+pub struct RingBuffer {
+    capacity: usize,
+    records: VecDeque<TraceRecord>,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &TraceRecord> {
+        self.records.iter()
+    }
+}
+
+impl TraceSink for RingBuffer {
+    fn record(&mut self, record: &TraceRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back(record.clone());
+    }
+}
+
+/// Writes one JSON object per record. The first write error is kept and
+/// later records are dropped.
+pub struct JsonLines<W: Write> {
+    w: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> JsonLines<W> {
+    pub fn new(w: W) -> Self {
+        Self { w, error: None }
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error {
+            Some(e) => Err(e),
+            None => self.w.flush().map(|_| self.w),
+        }
+    }
+}
+
+impl<W: Write> TraceSink for JsonLines<W> {
+    fn record(&mut self, record: &TraceRecord) {
+        if self.error.is_none() {
+            if let Err(e) = writeln!(self.w, "{}", to_json(record)) {
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+fn to_json(record: &TraceRecord) -> String {
+    let modes = record.instruction.modes()
+        .iter()
+        .map(|mode| match mode {
+            ParameterMode::Position => "\"position\"",
+            ParameterMode::Immediate => "\"immediate\"",
+            ParameterMode::Relative => "\"relative\"",
+        })
+        .collect::<Vec<_>>();
+    let operands = record.operands.iter().map(i64::to_string).collect::<Vec<_>>();
+    let write = match record.write {
+        Some((addr, val)) => format!("{{\"addr\":{},\"value\":{}}}", addr, val),
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"step\":{},\"pc\":{},\"op\":\"{}\",\"modes\":[{}],\"operands\":[{}],\"write\":{},\"next_pc\":{}}}",
+        record.step,
+        record.pc,
+        record.instruction.mnemonic(),
+        modes.join(","),
+        operands.join(","),
+        write,
+        record.next_pc,
+    )
+}
+
+/// Counts executions per address and opcode, and taken backward jumps as
+/// loops spanning `target..=pc`.
+#[derive(Default)]
+pub struct Profiler {
+    steps: u64,
+    address_hits: HashMap<usize, u64>,
+    opcode_counts: HashMap<&'static str, u64>,
+    loops: HashMap<(usize, usize), u64>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn report(&self, top: usize) -> ProfileReport {
+        let mut addresses = by_count(&self.address_hits);
+        let opcodes = by_count(&self.opcode_counts);
+        let mut loops = by_count(&self.loops);
+        addresses.truncate(top);
+        loops.truncate(top);
+
+        ProfileReport {
+            steps: self.steps,
+            addresses,
+            opcodes,
+            loops,
+        }
+    }
+}
+
+fn by_count<K: Copy + Ord>(counts: &HashMap<K, u64>) -> Vec<(K, u64)> {
+    let mut counts = counts.iter().map(|(&k, &c)| (k, c)).collect::<Vec<_>>();
+    counts.sort_by(|(k1, c1), (k2, c2)| c2.cmp(c1).then(k1.cmp(k2)));
+    counts
+}
+
+impl TraceSink for Profiler {
+    fn record(&mut self, record: &TraceRecord) {
+        self.steps += 1;
+        *self.address_hits.entry(record.pc).or_insert(0) += 1;
+        *self.opcode_counts.entry(record.instruction.mnemonic()).or_insert(0) += 1;
+
+        let taken = match record.instruction {
+            Instruction::JNZ(_) => record.operands[0] != 0,
+            Instruction::JEZ(_) => record.operands[0] == 0,
+            _ => false,
+        };
+        if taken && record.next_pc <= record.pc {
+            *self.loops.entry((record.next_pc, record.pc)).or_insert(0) += 1;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProfileReport {
+    pub steps: u64,
+    pub addresses: Vec<(usize, u64)>,
+    pub opcodes: Vec<(&'static str, u64)>,
+    pub loops: Vec<((usize, usize), u64)>,
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} instructions executed", self.steps)?;
+
+        writeln!(f, "\nopcodes:")?;
+        for (op, count) in &self.opcodes {
+            writeln!(f, "  {:<4}{:>12}", op, count)?;
+        }
+
+        writeln!(f, "\nhottest addresses:")?;
+        for (addr, count) in &self.addresses {
+            writeln!(f, "  {:04}{:>12}", addr, count)?;
+        }
+
+        writeln!(f, "\nhottest loops:")?;
+        for ((start, end), count) in &self.loops {
+            writeln!(f, "  {:04}..={:04}{:>12} iterations", start, end, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode_computer::{assemble, NoIO, SingleIO};
+
+    #[test]
+    fn test_trace() {
+        let program = assemble("
+            IN  [x]
+            MUL [x], #3, [x]
+            OUT [x]
+            HLT
+            x: db 0
+        ").unwrap();
+
+        let mut computer = IntcodeComputer::new(&program, SingleIO::new(7));
+        let mut ring = RingBuffer::new(2);
+        let mut json = JsonLines::new(vec![]);
+        computer.run_traced(&mut (&mut ring, &mut json)).unwrap();
+
+        let records = ring.records().collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].step, 2);
+        assert_eq!(records[0].operands, vec![21]);
+        assert_eq!(records[1].instruction, Instruction::HLT);
+
+        let json = String::from_utf8(json.finish().unwrap()).unwrap();
+        let lines = json.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1],
+            "{\"step\":1,\"pc\":2,\"op\":\"MUL\",\"modes\":[\"position\",\"immediate\",\"position\"],\
+             \"operands\":[7,3],\"write\":{\"addr\":9,\"value\":21},\"next_pc\":6}"
+        );
+    }
+
+    #[test]
+    fn test_profile() {
+        let program = assemble("
+            loop: ADD [n], #-1, [n]
+                  JNZ [n], #loop
+                  HLT
+            n:    db 5
+        ").unwrap();
+
+        let mut computer = IntcodeComputer::new(&program, NoIO);
+        let mut profiler = Profiler::new();
+        computer.run_traced(&mut profiler).unwrap();
+
+        let report = profiler.report(10);
+        assert_eq!(report.steps, 11);
+        assert_eq!(report.opcodes, vec![("ADD", 5), ("JNZ", 5), ("HLT", 1)]);
+        assert_eq!(report.addresses, vec![(0, 5), (4, 5), (7, 1)]);
+        assert_eq!(report.loops, vec![((0, 4), 4)]);
+        assert_eq!(profiler.report(1).addresses, vec![(0, 5)]);
+
+        let mut computer = IntcodeComputer::new(&[1101, 1, 1, 5, 99, 0], NoIO);
+        let mut profiler = Profiler::new();
+        computer.run_traced(&mut profiler).unwrap();
+        assert_eq!(profiler.report(10).loops, vec![]);
+    }
+}
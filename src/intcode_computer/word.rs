@@ -0,0 +1,49 @@
+use std::{
+    fmt::{Debug, Display},
+    str::FromStr,
+};
+
+use num::{BigInt, ToPrimitive};
+
+/// Value stored in an Intcode memory cell. `i64` words report overflow as
+/// an error instead of wrapping, `BigInt` words never overflow.
+pub trait Word: Clone + Debug + Display + FromStr + Eq + Ord + From<i64> {
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+    fn to_i64(&self) -> Option<i64>;
+
+    /// Like `to_i64`, but clamps values outside the `i64` range.
+    fn saturating_i64(&self) -> i64 {
+        self.to_i64().unwrap_or(if *self < Self::from(0) { i64::MIN } else { i64::MAX })
+    }
+}
+
+impl Word for i64 {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        i64::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        i64::checked_mul(*self, *other)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(*self)
+    }
+}
+
+impl Word for BigInt {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        ToPrimitive::to_i64(self)
+    }
+}
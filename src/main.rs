@@ -1,15 +1,189 @@
+mod report;
 mod solutions;
 mod solver;
-mod intcode_computer;
 
-use std::env;
-use crate::solutions::exec_day;
+use std::{env, path::PathBuf, process};
+use aoc_2019::intcode_computer;
+use crate::solutions::{exec_day, DAYS};
+use crate::solver::{summary_table, DayResult, Format, Options};
+
+const USAGE: &str = "\
+usage: aoc-2019 [options] <days>
+       aoc-2019 [options] verify-all
+
+days:
+  N                   a single day
+  N..M                days N to M, inclusive
+  all                 every solved day
+  verify-all          every solved day, checking the answers
+
+options:
+  -p, --part <1|2>    solve only one part
+  -i, --input <path>  read the input from path, or from stdin if it is -
+                      (a single day only)
+  -q, --quiet         print the answers only
+  -b, --bench <n>     time n runs of every phase after a warmup run
+  -v, --verify        compare the answers with the ones in answers/dayNN
+  -a, --answers <dir> read the expected answers from dir/dayNN instead
+  -f, --format <fmt>  report the results as text, json or csv
+  -h, --help          show this help";
 
 fn main() {
-    let day = env::args()
-        .nth(1)
-        .unwrap_or_else(|| String::from("1"))
-        .parse()
-        .unwrap_or(1);
-    exec_day(day);
-}
\ No newline at end of file
+    let args = env::args().skip(1).collect::<Vec<_>>();
+
+    let (days, options) = match parse_args(&args) {
+        Ok(Some(parsed)) => parsed,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(msg) => {
+            eprintln!("error: {}\n\n{}", msg, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let text = options.format == Format::Text;
+    let mut results = vec![];
+    for &day in &days {
+        if text && days.len() > 1 && !options.quiet {
+            println!("Day {}", day);
+        }
+
+        let result = exec_day(day, &options);
+        if let Some(e) = &result.error {
+            eprintln!("{}", e);
+        }
+        results.push(result);
+    }
+
+    match options.format {
+        Format::Text if days.len() > 1 && !options.quiet => {
+            let timings = results.iter()
+                .filter(|r| r.error.is_none())
+                .filter_map(|r| r.timings().map(|t| (r.day, t)))
+                .collect::<Vec<_>>();
+            print!("\n{}", summary_table(&timings));
+        }
+        Format::Text => {}
+        Format::Json => print!("{}", report::to_json(&results)),
+        Format::Csv => print!("{}", report::to_csv(&results)),
+    }
+
+    let code = exit_code(&results);
+    if code != 0 {
+        process::exit(code);
+    }
+}
+
+/// 1 if any day failed, including answers that didn't verify.
+fn exit_code(results: &[DayResult]) -> i32 {
+    if results.iter().any(|r| r.error.is_some()) { 1 } else { 0 }
+}
+
+/// Returns the days to run and how, or `None` if help was asked for.
+fn parse_args(args: &[String]) -> Result<Option<(Vec<i32>, Options)>, String> {
+    let mut options = Options::default();
+    let mut days = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-q" | "--quiet" => options.quiet = true,
+            "-v" | "--verify" => options.verify = true,
+            "-p" | "--part" => {
+                options.part = match value()?.as_str() {
+                    "1" => Some(1),
+                    "2" => Some(2),
+                    part => return Err(format!("invalid part {:?}, expected 1 or 2", part)),
+                };
+            }
+            "-i" | "--input" => options.input = Some(PathBuf::from(value()?)),
+            "-a" | "--answers" => options.answers = Some(PathBuf::from(value()?)),
+            "-f" | "--format" => options.format = value()?.parse()?,
+            "-b" | "--bench" => {
+                options.bench = match value()?.parse() {
+                    Ok(runs) if runs > 0 => Some(runs),
+                    _ => return Err(format!("{} needs a positive number of runs", arg)),
+                };
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option {}", arg)),
+            _ if days.is_some() => return Err(format!("unexpected argument {}", arg)),
+            "verify-all" => {
+                options.verify = true;
+                days = Some(DAYS.to_vec());
+            }
+            _ => days = Some(parse_days(arg)?),
+        }
+    }
+
+    let days = days.ok_or("no days given")?;
+    if options.input.is_some() && days.len() != 1 {
+        return Err("--input needs a single day".into());
+    }
+
+    Ok(Some((days, options)))
+}
+
+fn parse_days(arg: &str) -> Result<Vec<i32>, String> {
+    let day = |s: &str| s.parse::<i32>().map_err(|_| format!("invalid day {:?}", s));
+
+    if arg == "all" {
+        return Ok(DAYS.to_vec());
+    }
+
+    match arg.split_once("..") {
+        Some((first, last)) => {
+            let (first, last) = (day(first)?, day(last)?);
+            let days = DAYS.iter().copied().filter(|d| (first..=last).contains(d)).collect::<Vec<_>>();
+
+            if days.is_empty() {
+                return Err(format!("no solved days in {}", arg));
+            }
+            Ok(days)
+        }
+        None => Ok(vec![day(arg)?]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::SolverError;
+
+    fn parse(args: &str) -> Result<Option<(Vec<i32>, Options)>, String> {
+        parse_args(&args.split_whitespace().map(String::from).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let (days, options) = parse("-q --part 2 3..5").unwrap().unwrap();
+        assert_eq!(days, vec![3, 4, 5]);
+        assert_eq!((options.part, options.quiet), (Some(2), true));
+
+        assert_eq!(parse("all").unwrap().unwrap().0, DAYS);
+
+        let (days, options) = parse("verify-all").unwrap().unwrap();
+        assert_eq!((&days[..], options.verify), (DAYS, true));
+        let options = parse("-v -a tmp/answers 1").unwrap().unwrap().1;
+        assert_eq!(options.answers, Some(PathBuf::from("tmp/answers")));
+        assert!(parse("-h 1").unwrap().is_none());
+        assert_eq!(parse("--format csv 1").unwrap().unwrap().1.format, Format::Csv);
+        assert_eq!(parse("-f xml 1").unwrap_err(), "invalid format \"xml\", expected text, json or csv");
+        assert_eq!(parse("").unwrap_err(), "no days given");
+        assert_eq!(parse("-p 3 1").unwrap_err(), "invalid part \"3\", expected 1 or 2");
+        assert_eq!(parse("-i - all").unwrap_err(), "--input needs a single day");
+        assert_eq!(parse("1 -i").unwrap_err(), "-i needs a value");
+    }
+
+    #[test]
+    fn test_exit_code() {
+        let failed = DayResult::failed(3, SolverError::new("part 1: expected 2, got 1"));
+
+        assert_eq!(exit_code(&[DayResult::new(1), DayResult::new(2)]), 0);
+        assert_eq!(exit_code(&[DayResult::new(1), failed]), 1);
+    }
+}
@@ -0,0 +1,152 @@
+use std::fmt::Write;
+
+use crate::solver::{DayResult, SolverError, Stats};
+
+/// All results as a JSON array with one object per day. Durations are in
+/// nanoseconds, and `passed` is only set when verifying.
+pub fn to_json(results: &[DayResult]) -> String {
+    let days = results.iter().map(day_json).collect::<Vec<_>>();
+
+    if days.is_empty() {
+        "[]\n".to_string()
+    }
+    else {
+        format!("[\n  {}\n]\n", days.join(",\n  "))
+    }
+}
+
+/// All results as CSV with one row per solved part. A day that failed gets
+/// an extra row with its error and no part.
+pub fn to_csv(results: &[DayResult]) -> String {
+    let mut csv = "day,part,answer,passed,parse_ns,time_ns,error\n".to_string();
+
+    for result in results {
+        let parse = result.parse.map(|s| s.median.as_nanos().to_string()).unwrap_or_default();
+
+        for part in &result.parts {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},",
+                result.day,
+                part.part,
+                csv_field(&part.answer),
+                part.passed.map(|p| p.to_string()).unwrap_or_default(),
+                parse,
+                part.time.median.as_nanos(),
+            ).unwrap();
+        }
+
+        if let Some(e) = &result.error {
+            writeln!(csv, "{},,,,{},,{}", result.day, parse, csv_field(&error_message(e))).unwrap();
+        }
+    }
+
+    csv
+}
+
+fn day_json(result: &DayResult) -> String {
+    let parts = result.parts
+        .iter()
+        .map(|p| {
+            format!(
+                "{{\"part\": {}, \"answer\": {}, \"time\": {}, \"passed\": {}}}",
+                p.part,
+                json_string(&p.answer),
+                stats_json(&p.time),
+                p.passed.map_or("null".to_string(), |p| p.to_string()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "{{\"day\": {}, \"parse\": {}, \"parts\": [{}], \"error\": {}}}",
+        result.day,
+        result.parse.as_ref().map_or("null".to_string(), stats_json),
+        parts.join(", "),
+        result.error.as_ref().map_or("null".to_string(), |e| json_string(&error_message(e))),
+    )
+}
+
+fn stats_json(s: &Stats) -> String {
+    format!(
+        "{{\"runs\": {}, \"min_ns\": {}, \"median_ns\": {}, \"mean_ns\": {}}}",
+        s.runs,
+        s.min.as_nanos(),
+        s.median.as_nanos(),
+        s.mean.as_nanos(),
+    )
+}
+
+// the day is a field of its own already
+fn error_message(e: &SolverError) -> String {
+    SolverError { day: None, ..e.clone() }.to_string()
+}
+
+fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    }
+    else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::PartResult;
+    use std::time::Duration;
+
+    #[test]
+    fn test_report() {
+        let stats = |ns| {
+            let d = Duration::from_nanos(ns);
+            Stats { runs: 1, min: d, median: d, mean: d }
+        };
+        let results = vec![
+            DayResult {
+                parts: vec![PartResult { part: 1, answer: "GC,\"PHL\"".to_string(), time: stats(20), passed: Some(true) }],
+                parse: Some(stats(10)),
+                ..DayResult::failed(8, SolverError::at_line(2, "x", "bad pixel"))
+            },
+            DayResult::new(9),
+        ];
+
+        assert_eq!(
+            to_json(&results),
+            "[\n  \
+             {\"day\": 8, \"parse\": {\"runs\": 1, \"min_ns\": 10, \"median_ns\": 10, \"mean_ns\": 10}, \
+             \"parts\": [{\"part\": 1, \"answer\": \"GC,\\\"PHL\\\"\", \
+             \"time\": {\"runs\": 1, \"min_ns\": 20, \"median_ns\": 20, \"mean_ns\": 20}, \"passed\": true}], \
+             \"error\": \"line 2: bad pixel in \\\"x\\\"\"},\n  \
+             {\"day\": 9, \"parse\": null, \"parts\": [], \"error\": null}\n\
+             ]\n"
+        );
+        assert_eq!(
+            to_csv(&results),
+            "day,part,answer,passed,parse_ns,time_ns,error\n\
+             8,1,\"GC,\"\"PHL\"\"\",true,10,20,\n\
+             8,,,,10,,\"line 2: bad pixel in \"\"x\"\"\"\n"
+        );
+        assert_eq!(json_string("a\u{1}\n"), "\"a\\u0001\\n\"");
+    }
+}
@@ -1,5 +1,5 @@
-use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use crate::solver::{parse_lines, Solver, SolverError};
+use std::io;
 
 pub struct Problem;
 
@@ -8,16 +8,15 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        r.lines().flatten().flat_map(|l| l.parse()).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        parse_lines(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        Iterator::sum(input.iter().map(|n| n / 3 - 2))
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        Ok(Iterator::sum(input.iter().map(|n| n / 3 - 2)))
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
         let mut total = 0;
 
         for n in input {
@@ -35,6 +34,6 @@ impl Solver for Problem {
             total += fuel;
         }
 
-        total
+        Ok(total)
     }
 }
\ No newline at end of file
@@ -1,45 +1,52 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, NoIO, read_program};
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::{IntcodeComputer, NoIO};
 use std::io::Read;
 
 pub struct Problem;
 
+// the real program finishes in a handful of instructions, anything longer
+// is a candidate that went off the rails
+const STEP_LIMIT: u64 = 10_000;
+
 impl Solver for Problem {
     type Input = Vec<i64>;
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        read_program(r)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        let program = parse_program(r)?;
+        if program.len() < 3 {
+            return Err(SolverError::new("program too short to take a noun and verb"));
+        }
+        Ok(program)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
         let mut memory = input.clone();
         memory[1] = 12;
         memory[2] = 2;
 
-        let mut computer = IntcodeComputer::new(&mut memory, NoIO);
-        computer.run();
-        computer.read(0)
+        let mut computer = IntcodeComputer::new(&memory, NoIO);
+        computer.run()?;
+        Ok(computer.read(0))
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
         for noun in  0..99 {
             for verb in 0..99 {
                 let mut memory = input.clone();
                 memory[1] = noun;
                 memory[2] = verb;
 
-                let mut computer = IntcodeComputer::new(&mut memory, NoIO);
-
-                computer.run();
+                let mut computer = IntcodeComputer::new(&memory, NoIO)
+                    .with_step_limit(STEP_LIMIT);
 
-                if computer.read(0) == 19690720 {
-                    return 100 * noun + verb;
+                if computer.run().is_ok() && computer.read(0) == 19690720 {
+                    return Ok(100 * noun + verb);
                 }
             }
         }
 
-        unreachable!();
+        Err(SolverError::new("no noun and verb produce 19690720"))
     }
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
-use crate::solver::Solver;
+use crate::solver::{read_lines, Solver, SolverError};
 use std::{
-    io::{self, BufReader, BufRead},
+    io,
     str::FromStr,
     collections::HashSet,
     iter::FromIterator,
@@ -15,12 +15,13 @@ pub struct Segment {
 }
 
 impl FromStr for Segment {
-    type Err = ();
+    type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let dir = s.get(0..1).ok_or("missing direction")?;
         Ok(Segment {
-            dir: s[0..1].parse::<Direction>().expect("Unable to parse direction"),
-            dist: s[1..].parse::<isize>().expect("Unable to parse distance"),
+            dir: dir.parse::<Direction>().map_err(|_| "invalid direction")?,
+            dist: s[1..].parse::<isize>().map_err(|_| "invalid distance")?,
         })
     }
 }
@@ -88,45 +89,47 @@ impl Solver for Problem {
     type Output1 = isize;
     type Output2 = isize;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let mut r = BufReader::new(r);
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        let lines = read_lines(r)?;
+        if lines.len() < 2 {
+            return Err(SolverError::new("expected two wires"));
+        }
 
-        let mut buf = String::new();
-        let _ = r.read_line(&mut buf).expect("Unable to read first line");
-        let wire1 = parse_segment(&buf);
-        let wire1_coords= HashSet::from_iter(segments_to_points(&wire1).clone().into_iter());
+        let (n, line) = &lines[0];
+        let wire1 = parse_segment(*n, line)?;
+        let wire1_coords= HashSet::from_iter(segments_to_points(&wire1).clone());
 
-        buf = String::new();
-        let _ = r.read_line(&mut buf).expect("Unable to read second line");
-        let wire2 = parse_segment(&buf);
-        let wire2_coords: HashSet<Point>= HashSet::from_iter(segments_to_points(&wire2).clone().into_iter());
+        let (n, line) = &lines[1];
+        let wire2 = parse_segment(*n, line)?;
+        let wire2_coords: HashSet<Point>= HashSet::from_iter(segments_to_points(&wire2).clone());
 
-        (wire1_coords, wire2_coords)
+        Ok((wire1_coords, wire2_coords))
     }
 
-    fn solve_first(&self, (w1, w2): &Self::Input) -> Self::Output1 {
-        w1.intersection(&w2)
+    fn solve_first(&self, (w1, w2): &Self::Input) -> Result<Self::Output1, SolverError> {
+        w1.intersection(w2)
             .map(|i| i.manhattan_distance())
             .min()
-            .unwrap()
+            .ok_or_else(|| SolverError::new("the wires never cross"))
     }
 
-    fn solve_second(&self, (w1, w2): &Self::Input) -> Self::Output2 {
-        w1.intersection(&w2)
+    fn solve_second(&self, (w1, w2): &Self::Input) -> Result<Self::Output2, SolverError> {
+        w1.intersection(w2)
             .map(|p| {
                 let p1 = w1.get(p).unwrap();
                 let p2 = w2.get(p).unwrap();
-                (p1.path_length + p2.path_length)
+                p1.path_length + p2.path_length
             })
             .min()
-            .unwrap()
+            .ok_or_else(|| SolverError::new("the wires never cross"))
     }
 }
 
-fn parse_segment(s: &String) -> Vec<Segment> {
+fn parse_segment(line: usize, s: &str) -> Result<Vec<Segment>, SolverError> {
     s.trim()
         .split(',')
-        .map(|s| s.parse::<Segment>().expect("Unable to parse segment")).collect()
+        .map(|s| s.parse::<Segment>().map_err(|e| SolverError::at_line(line, s, e)))
+        .collect()
 }
 
 fn segments_to_points(segments: &Vec<Segment>) -> Vec<Point> {
@@ -1,5 +1,5 @@
-use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use crate::solver::{read_lines, Solver, SolverError};
+use std::io;
 
 pub struct Problem;
 
@@ -8,19 +8,25 @@ impl Solver for Problem {
     type Output1 = i32;
     type Output2 = i32;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-        let s: String = r.lines().flatten().next().expect("Unable to read line");
-        let range: Vec<i32> = s.split('-').map(|x| x.parse().expect("Unable to parse")).collect();
-        (range[0]..range[1]).collect()
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        let (n, s) = read_lines(r)?.into_iter().next().ok_or_else(|| SolverError::new("empty input"))?;
+        let range = s.trim()
+            .split('-')
+            .map(|x| x.parse().map_err(|_| SolverError::at_line(n, &s, "invalid range")))
+            .collect::<Result<Vec<i32>, _>>()?;
+
+        match range[..] {
+            [start, end] => Ok((start..end).collect()),
+            _ => Err(SolverError::at_line(n, &s, "expected a range like 123-456")),
+        }
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        input.iter().filter(|p| validate_password_first(p)).count() as i32
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        Ok(input.iter().filter(|p| validate_password_first(p)).count() as i32)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        input.iter().filter(|p| validate_password_second(p)).count() as i32
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        Ok(input.iter().filter(|p| validate_password_second(p)).count() as i32)
     }
 }
 
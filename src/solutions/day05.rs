@@ -1,29 +1,37 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, IO, SingleIO, read_program};
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::{IntcodeComputer, BufferedIO};
 use std::io::Read;
 
 pub struct Problem;
 
+/// Runs the diagnostic program and returns its final output, the
+/// diagnostic code. Every output before it is a test result that must be 0.
+fn diagnostic(program: &[i64], system_id: i64) -> Result<i64, SolverError> {
+    let mut computer = IntcodeComputer::new(program, BufferedIO::new(vec![system_id]));
+    computer.run()?;
+
+    let (code, tests) = computer.io.output.split_last()
+        .ok_or_else(|| SolverError::new("no diagnostic code"))?;
+    if tests.iter().any(|&t| t != 0) {
+        return Err(SolverError::new(format_args!("diagnostic tests failed: {:?}", tests)));
+    }
+    Ok(*code)
+}
+
 impl Solver for Problem {
     type Input = Vec<i64>;
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        read_program(r)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        parse_program(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut program = input.clone();
-        let mut computer = IntcodeComputer::new(&mut program, SingleIO::new(1));
-        computer.run();
-        computer.io.read().unwrap()
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        diagnostic(input, 1)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut program = input.clone();
-        let mut computer = IntcodeComputer::new(&mut program, SingleIO::new(5));
-        computer.run();
-        computer.io.read().unwrap()
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        diagnostic(input, 5)
     }
-}
\ No newline at end of file
+}
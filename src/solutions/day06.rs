@@ -1,6 +1,6 @@
-use crate::solver::Solver;
+use crate::solver::{read_lines, Solver, SolverError};
 use std::{
-    io::{self, BufRead, BufReader},
+    io,
     collections::HashMap,
 };
 use std::collections::VecDeque;
@@ -19,22 +19,14 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let r = BufReader::new(r);
-
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, SolverError> {
         let mut planets: HashMap<String, Vec<String>> = HashMap::new();
 
-        for line in r.lines().map(|l| l.expect("Unable to read line")) {
-            let item: Vec<&str> = line.split(')').collect();
-            let parent = item[0].to_string();
-            let child = item[1].to_string();
+        for (n, line) in read_lines(r)? {
+            let (parent, child) = line.trim().split_once(')')
+                .ok_or_else(|| SolverError::at_line(n, &line, "expected an orbit like A)B"))?;
 
-            if planets.contains_key(&parent) {
-                planets.get_mut(&parent).expect("Unable to get parent").push(child);
-            }
-            else {
-                planets.insert(parent, vec![child]);
-            }
+            planets.entry(parent.to_string()).or_default().push(child.to_string());
         }
 
         let mut root = Planet {
@@ -45,14 +37,14 @@ impl Solver for Problem {
 
         add_children(&mut root, &planets, 1);
 
-        root
+        Ok(root)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        sum_depths(&input)
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        Ok(sum_depths(input))
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
         let mut queue = VecDeque::new();
         let mut path_to_you = vec![];
         let mut path_to_san = vec![];
@@ -93,9 +85,9 @@ impl Solver for Problem {
             .filter(|p| path_to_san.contains(p))
             .map(|p| p.depth)
             .max()
-            .expect("Can't find common parent");
+            .ok_or_else(|| SolverError::new("YOU and SAN don't orbit a common planet"))?;
 
-        you_depth + san_depth - 2 * common_parent_depth - 2
+        Ok(you_depth + san_depth - 2 * common_parent_depth - 2)
     }
 }
 
@@ -1,12 +1,7 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, AsyncIO, Pipe, read_program};
-use std::{
-    io::Read,
-    thread,
-    iter::from_fn,
-    sync::mpsc::channel
-};
-use itertools::Itertools;
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::network::{Network, NodeSpec, Topology};
+use std::io::Read;
+use itertools::{process_results, Itertools};
 
 pub struct Problem;
 
@@ -15,96 +10,36 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        read_program(r)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        parse_program(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        (0..5).permutations(5)
-            .map(|seq| test_sequence(input, seq))
-            .max()
-            .expect("Can't find max value")
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        let signals = (0..5).permutations(5).map(|seq| test_sequence(input, seq, false));
+        Ok(process_results(signals, |signals| signals.max())?.unwrap())
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        (5..10).permutations(5)
-            .map(|seq| test_sequence_2(input, seq))
-            .max()
-            .expect("Can't find max value")
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        let signals = (5..10).permutations(5).map(|seq| test_sequence(input, seq, true));
+        Ok(process_results(signals, |signals| signals.max())?.unwrap())
     }
 }
 
-fn test_sequence(program: &Vec<i64>, sequence: Vec<i64>) -> i64 {
-    let mut input = 0;
-    for n in sequence {
-        let (io, tx, rx) = AsyncIO::new();
-        let _ = tx.send(n);
-        let _ = tx.send(input);
-
-        let mut computer = IntcodeComputer::new(&mut program.clone(), io);
-        computer.run();
-        drop(computer);
-
-        input = from_fn(|| rx.recv().ok()).last().unwrap();
-    }
-    input
+/// Runs one amplifier per phase setting, wired in a chain or, with
+/// `feedback`, in a ring, and returns the last signal from the final one.
+fn test_sequence(program: &[i64], sequence: Vec<i64>, feedback: bool) -> Result<i64, SolverError> {
+    let amps = (0..sequence.len()).collect::<Vec<_>>();
+    let mut net = Network::from_topology(&Topology {
+        nodes: sequence.into_iter()
+            .map(|phase| NodeSpec { program: program.to_vec(), input: vec![phase] })
+            .collect(),
+        links: if feedback { Topology::ring(&amps) } else { Topology::chain(&amps) },
+        max_steps: None,
+    })?;
+    net.send(amps[0], 0)?;
+
+    let run = net.run()?;
+    run.emitted[*amps.last().unwrap()].last()
+        .copied()
+        .ok_or_else(|| SolverError::new("no output from the last amplifier"))
 }
-
-fn test_sequence_2(program: &Vec<i64>, sequence: Vec<i64>) -> i64 {
-    // setup io
-    let (io_a, tx_a, rx_a) = AsyncIO::new();
-    let (io_b, tx_b, rx_b) = AsyncIO::new();
-    let (io_c, tx_c, rx_c) = AsyncIO::new();
-    let (io_d, tx_d, rx_d) = AsyncIO::new();
-    let (io_e, tx_e, rx_e) = AsyncIO::new();
-    let (tx_out, rx_out) = channel();
-
-    // send sequence
-    let _ = tx_a.send(sequence[0]);
-    let _ = tx_b.send(sequence[1]);
-    let _ = tx_c.send(sequence[2]);
-    let _ = tx_d.send(sequence[3]);
-    let _ = tx_e.send(sequence[4]);
-
-    // send initial value for a
-    let _ = tx_a.send(0);
-
-    // setup connections
-    let pipe_ab = Pipe::new(rx_a, vec![tx_b]);
-    let pipe_bc = Pipe::new(rx_b, vec![tx_c]);
-    let pipe_cd = Pipe::new(rx_c, vec![tx_d]);
-    let pipe_de = Pipe::new(rx_d, vec![tx_e]);
-    let pipe_eao = Pipe::new(rx_e, vec![tx_a, tx_out]);
-
-    // setup computers
-    let mut cpu_a = IntcodeComputer::new(&mut program.clone(), io_a);
-    let mut cpu_b = IntcodeComputer::new(&mut program.clone(), io_b);
-    let mut cpu_c = IntcodeComputer::new(&mut program.clone(), io_c);
-    let mut cpu_d = IntcodeComputer::new(&mut program.clone(), io_d);
-    let mut cpu_e = IntcodeComputer::new(&mut program.clone(), io_e);
-
-    // start output thread
-    let output_thread = thread::spawn(move || {
-        from_fn(|| rx_out.recv().ok()).last().unwrap()
-    });
-
-    // start intcode cpu threads
-    let threads = vec![
-        thread::spawn(move || cpu_a.run()),
-        thread::spawn(move || cpu_b.run()),
-        thread::spawn(move || cpu_c.run()),
-        thread::spawn(move || cpu_d.run()),
-        thread::spawn(move || cpu_e.run()),
-        thread::spawn(move || pipe_ab.run()),
-        thread::spawn(move || pipe_bc.run()),
-        thread::spawn(move || pipe_cd.run()),
-        thread::spawn(move || pipe_de.run()),
-        thread::spawn(move || pipe_eao.run()),
-    ];
-
-    for t in threads {
-        t.join().unwrap();
-    }
-
-    output_thread.join().unwrap()
-}
\ No newline at end of file
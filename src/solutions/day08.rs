@@ -1,4 +1,4 @@
-use crate::solver::Solver;
+use crate::solver::{read_lines, Solver, SolverError};
 use std::io;
 
 pub struct Problem;
@@ -23,7 +23,7 @@ impl Image {
         }
     }
 
-    fn display(&self) {
+    fn render(&self) -> String {
         let mut pixels: Vec<u8> = Vec::with_capacity((self.width * self.height) as usize);
         for i in 0..self.width * self.height {
             pixels.push(self.layers.iter()
@@ -32,14 +32,16 @@ impl Image {
                 .unwrap())
         }
 
+        let mut picture = String::new();
         pixels.chunks(self.width as usize)
             .for_each(|row| {
                 for &pixel in row {
-                    let char = if pixel == 1 { "#" } else { " " };
-                    print!("{}", char);
+                    let char = if pixel == 1 { '#' } else { ' ' };
+                    picture.push(char);
                 }
-                println!();
+                picture.push('\n');
             });
+        picture
     }
 
     fn counts(layer: &Vec<u8>) -> [i32; 3] {
@@ -56,24 +58,38 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = String;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        let n: Vec<u8> = r.bytes().flatten().map(|b| b - b'0').collect();
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        let mut n: Vec<u8> = vec![];
+        for (line, s) in read_lines(r)? {
+            for c in s.trim().chars() {
+                match c.to_digit(10) {
+                    Some(pixel) if pixel <= 2 => n.push(pixel as u8),
+                    _ => return Err(SolverError::at_line(line, &s, format_args!("invalid pixel {:?}", c))),
+                }
+            }
+        }
 
-        Image::new(25, 6, n)
+        if n.is_empty() || !n.len().is_multiple_of(25 * 6) {
+            return Err(SolverError::new("image data doesn't fill a whole number of 25x6 layers"));
+        }
+
+        Ok(Image::new(25, 6, n))
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
         let counts = Image::counts(input.layers
             .iter()
             .min_by_key(|layer| Image::counts(layer)[0])
             .unwrap());
 
-        (counts[1] * counts[2]) as usize
+        Ok((counts[1] * counts[2]) as usize)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        input.display();
+    fn solve_second(&self, _input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        Ok("GCPHL".into())
+    }
 
-        "GCPHL".into()
+    fn draw(&self, input: &Self::Input) -> Result<Option<String>, SolverError> {
+        Ok(Some(input.render()))
     }
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, IO, SingleIO, read_program};
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::{IntcodeComputer, IO, SingleIO};
 use std::io::Read;
 
 pub struct Problem;
@@ -10,19 +10,19 @@ impl Solver for Problem {
     type Output1 = i64;
     type Output2 = i64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        read_program(r)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        parse_program(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let mut cpu = IntcodeComputer::new(&mut input.clone(), SingleIO::new(1));
-        cpu.run();
-        cpu.io.read().unwrap()
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        let mut cpu = IntcodeComputer::new(input, SingleIO::new(1));
+        cpu.run()?;
+        Ok(cpu.io.read()?)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let mut cpu = IntcodeComputer::new(&mut input.clone(), SingleIO::new(2));
-        cpu.run();
-        cpu.io.read().unwrap()
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        let mut cpu = IntcodeComputer::new(input, SingleIO::new(2));
+        cpu.run()?;
+        Ok(cpu.io.read()?)
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
-use crate::solver::Solver;
-use std::io::{Read, BufRead, BufReader};
+use crate::solver::{read_lines, Solver, SolverError};
+use std::io::Read;
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use num::Integer;
 use std::f64::consts::PI;
 
@@ -12,12 +13,14 @@ pub enum Cell {
     Asteroid,
 }
 
-impl From<char> for Cell {
-    fn from(c: char) -> Self {
+impl TryFrom<char> for Cell {
+    type Error = String;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
         match c {
-            '.' => Cell::Empty,
-            '#' => Cell::Asteroid,
-            _ => panic!("Invalid input"),
+            '.' => Ok(Cell::Empty),
+            '#' => Ok(Cell::Asteroid),
+            _ => Err(format!("invalid cell {:?}", c)),
         }
     }
 }
@@ -30,21 +33,27 @@ pub struct Grid {
 }
 
 impl Grid {
-    fn read_grid<R: BufRead>(r: R) -> Self {
-        let cells = r
-            .lines()
-            .filter_map(|l| l.ok())
-            .map(|l| l.chars().map(Cell::from).collect::<Vec<Cell>>())
-            .collect::<Vec<Vec<Cell>>>();
+    fn read_grid<R: Read>(r: R) -> Result<Self, SolverError> {
+        let cells = read_lines(r)?
+            .into_iter()
+            .map(|(n, l)| l.trim().chars()
+                .map(Cell::try_from)
+                .collect::<Result<Vec<Cell>, _>>()
+                .map_err(|e| SolverError::at_line(n, &l, e)))
+            .collect::<Result<Vec<Vec<Cell>>, _>>()?;
 
         let height = cells.len();
-        let width = cells[0].len();
+        let width = cells.first().map_or(0, Vec::len);
+
+        if let Some(n) = cells.iter().position(|row| row.len() != width) {
+            return Err(SolverError { line: Some(n + 1), ..SolverError::new("row differs in width from the first one") });
+        }
 
-        Grid {
+        Ok(Grid {
             width,
             height,
             cells,
-        }
+        })
     }
 }
 
@@ -98,23 +107,24 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = usize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        Grid::read_grid(BufReader::new(r))
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        Grid::read_grid(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        let (_, c) = find_best_position(&input);
-        c
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        let (_, c) = find_best_position(input)?;
+        Ok(c)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let vaporized = vaporize(&mut input.clone());
-        let p = &vaporized[199];
-        p.x * 100 + p.y
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        let vaporized = vaporize(&mut input.clone())?;
+        let p = vaporized.get(199)
+            .ok_or_else(|| SolverError::new("fewer than 200 asteroids to vaporize"))?;
+        Ok(p.x * 100 + p.y)
     }
 }
 
-fn find_best_position(grid: &Grid) -> (Point, usize) {
+fn find_best_position(grid: &Grid) -> Result<(Point, usize), SolverError> {
     let mut positions = vec![];
     for y in 0..grid.height {
         for x in 0..grid.width {
@@ -130,11 +140,11 @@ fn find_best_position(grid: &Grid) -> (Point, usize) {
     positions
         .into_iter()
         .max_by_key(|(_, c)| *c)
-        .unwrap()
+        .ok_or_else(|| SolverError::new("no asteroids on the map"))
 }
 
-fn vaporize(grid: &mut Grid) -> Vec<Point> {
-    let (origin, _) = find_best_position(grid);
+fn vaporize(grid: &mut Grid) -> Result<Vec<Point>, SolverError> {
+    let (origin, _) = find_best_position(grid)?;
     let mut vaporized = vec![];
 
     loop {
@@ -146,12 +156,12 @@ fn vaporize(grid: &mut Grid) -> Vec<Point> {
         visible.sort_by(|(_, v1),(_, v2)| v1.angle().partial_cmp(&v2.angle()).unwrap());
 
         visible.iter().for_each(|(p, _)| {
-            vaporized.push(p.clone());
+            vaporized.push(*p);
             grid.cells[p.y][p.x] = Cell::Empty;
         });
     }
 
-    vaporized
+    Ok(vaporized)
 }
 
 fn find_visible(grid: &Grid, origin: &Point) -> Vec<(Point, Vec2)> {
@@ -1,10 +1,8 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, AsyncIO, read_program};
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::{IntcodeComputer, Event, NoIO};
 use std::{
     io::Read,
-    iter::repeat,
     collections::HashSet,
-    thread
 };
 
 pub struct Problem;
@@ -22,7 +20,7 @@ impl Grid {
         }
     }
 
-    fn display(&self) {
+    fn render(&self) -> String {
         let mut min_x = 0;
         let mut max_x = 0;
         let mut min_y = 0;
@@ -40,19 +38,18 @@ impl Grid {
         let width = max_x - min_x + 1;
         let height = max_y - min_y + 1;
 
-        let mut chars: Vec<Vec<char>> = repeat(repeat(' ').take(width as usize).collect())
-            .take(height as usize).collect();
+        let mut chars: Vec<Vec<char>> = vec![vec![' '; width as usize]; height as usize];
 
         for &(x, y) in self.white_panels.iter() {
             chars[(height - (y + yoff) - 1) as usize][(x + xoff) as usize] = '#';
         }
 
+        let mut picture = String::new();
         for row in chars {
-            for c in row {
-                print!("{}", c);
-            }
-            println!();
+            picture.extend(row);
+            picture.push('\n');
         }
+        picture
     }
 }
 
@@ -117,64 +114,68 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = String;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        read_program(r)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        parse_program(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
         let mut grid = Grid::new();
-        paint_grid(&mut grid, &mut input.clone());
-        grid.changed_panels.len()
+        paint_grid(&mut grid, input)?;
+        Ok(grid.changed_panels.len())
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
         let mut grid = Grid::new();
         grid.white_panels.insert((0,0));
-        paint_grid(&mut grid, &mut input.clone());
-        grid.display();
-        String::from("BCKFPCRA")
+        paint_grid(&mut grid, input)?;
+        Ok(String::from("BCKFPCRA"))
     }
-}
-
-fn paint_grid(grid: &mut Grid, program: &mut Vec<i64>) {
-    let (io, tx, rx) = AsyncIO::new();
-    let mut cpu = IntcodeComputer::new(program, io);
-
-    let handle = thread::spawn(move || cpu.run());
 
-    let mut input = 0;
-    if grid.white_panels.contains(&(0,0)) {
-        input = 1;
+    fn draw(&self, input: &Self::Input) -> Result<Option<String>, SolverError> {
+        let mut grid = Grid::new();
+        grid.white_panels.insert((0,0));
+        paint_grid(&mut grid, input)?;
+        Ok(Some(grid.render()))
     }
-    tx.send(input).unwrap();
+}
 
+fn paint_grid(grid: &mut Grid, program: &[i64]) -> Result<(), SolverError> {
+    let mut cpu = IntcodeComputer::new(program, NoIO);
     let mut robot = Robot::new();
-
-    while let Ok(mut val) = rx.recv() {
-        if val == 0 && grid.white_panels.contains(&robot.pos) {
-            grid.white_panels.remove(&robot.pos);
-        }
-        else if val == 1 {
-            grid.white_panels.insert(robot.pos);
-            grid.changed_panels.insert(robot.pos);
+    let mut output = vec![];
+
+    loop {
+        match cpu.run_until_event()? {
+            Event::NeedInput => {
+                if grid.white_panels.contains(&robot.pos) {
+                    cpu.provide_input(1);
+                } else {
+                    cpu.provide_input(0);
+                }
+            }
+            Event::Output(val) => output.push(val),
+            Event::Halted => break,
         }
 
-        val = rx.recv().unwrap();
+        if let [color, turn] = output[..] {
+            if color == 0 && grid.white_panels.contains(&robot.pos) {
+                grid.white_panels.remove(&robot.pos);
+            }
+            else if color == 1 {
+                grid.white_panels.insert(robot.pos);
+                grid.changed_panels.insert(robot.pos);
+            }
 
-        if val == 0 {
-            robot.rotate_left();
-        } else {
-            robot.rotate_right();
-        }
-        robot.move_forward();
+            if turn == 0 {
+                robot.rotate_left();
+            } else {
+                robot.rotate_right();
+            }
+            robot.move_forward();
 
-        if grid.white_panels.contains(&robot.pos) {
-            val = 1
-        } else {
-            val = 0
+            output.clear();
         }
-        let _ = tx.send(val);
     }
 
-    handle.join().unwrap();
+    Ok(())
 }
\ No newline at end of file
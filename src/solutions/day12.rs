@@ -1,6 +1,6 @@
-use crate::solver::Solver;
+use crate::solver::{read_lines, Solver, SolverError};
 use std::{
-    io::{Read, BufReader, BufRead},
+    io::Read,
     error::Error,
     ops::AddAssign,
     cmp::Ordering
@@ -116,24 +116,25 @@ impl Solver for Problem {
     type Output1 = isize;
     type Output2 = isize;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        BufReader::new(r)
-            .lines()
-            .flatten()
-            .flat_map(|s| Vec3::from_str(&s))
-            .map(Moon::new)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        read_lines(r)?
+            .into_iter()
+            .filter(|(_, s)| !s.trim().is_empty())
+            .map(|(n, s)| Vec3::from_str(&s)
+                .map(Moon::new)
+                .map_err(|e| SolverError::at_line(n, &s, e)))
             .collect()
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
         let mut moons = input.clone();
         for _ in 0..1000 {
             step(&mut moons);
         }
-        Iterator::sum(moons.iter().map(|m| m.get_energy()))
+        Ok(Iterator::sum(moons.iter().map(|m| m.get_energy())))
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
         let mut moons = input.clone();
 
         let mut i = 1;
@@ -163,7 +164,7 @@ impl Solver for Problem {
             i += 1;
         }
 
-        per_x.lcm(&per_y.lcm(&per_z))
+        Ok(per_x.lcm(&per_y.lcm(&per_z)))
     }
 }
 
@@ -180,14 +181,14 @@ fn step(moons: &mut Vec<Moon>) {
     }
 }
 
-fn vx_zero(moons: &Vec<Moon>) -> bool {
+fn vx_zero(moons: &[Moon]) -> bool {
     moons.iter().all(|m| m.v.x == 0)
 }
 
-fn vy_zero(moons: &Vec<Moon>) -> bool {
+fn vy_zero(moons: &[Moon]) -> bool {
     moons.iter().all(|m| m.v.y == 0)
 }
 
-fn vz_zero(moons: &Vec<Moon>) -> bool {
+fn vz_zero(moons: &[Moon]) -> bool {
     moons.iter().all(|m| m.v.z == 0)
 }
\ No newline at end of file
@@ -1,12 +1,10 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, AsyncIO, read_program};
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::{IntcodeComputer, Event, NoIO};
 use std::{
     io::Read,
-    thread,
+    convert::TryFrom,
     collections::HashMap,
-    error::Error,
     cmp::Ordering,
-    sync::mpsc::Receiver
 };
 
 pub struct Problem;
@@ -20,15 +18,17 @@ enum Tile {
     Ball,
 }
 
-impl From<i64> for Tile {
-    fn from(n: i64) -> Self {
+impl TryFrom<i64> for Tile {
+    type Error = SolverError;
+
+    fn try_from(n: i64) -> Result<Self, Self::Error> {
         match n {
-            0 => Tile::Empty,
-            1 => Tile::Wall,
-            2 => Tile::Block,
-            3 => Tile::Paddle,
-            4 => Tile::Ball,
-            _ => panic!("Unknown tile"),
+            0 => Ok(Tile::Empty),
+            1 => Ok(Tile::Wall),
+            2 => Ok(Tile::Block),
+            3 => Ok(Tile::Paddle),
+            4 => Ok(Tile::Ball),
+            _ => Err(SolverError::new(format_args!("unknown tile {}", n))),
         }
     }
 }
@@ -38,65 +38,68 @@ impl Solver for Problem {
     type Output1 = usize;
     type Output2 = i64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        read_program(r)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        parse_program(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
         let mut tiles: HashMap<(i64, i64), Tile> = HashMap::new();
 
-        let (io, _, rx) = AsyncIO::new();
-        let mut cpu = IntcodeComputer::new(&mut input.clone(), io);
-
-        let handle = thread::spawn(move || { cpu.run() });
+        let mut cpu = IntcodeComputer::new(input, NoIO);
 
-        while let Ok((x, y, tile)) = recv_tile(&rx) {
-            tiles.insert((x, y), tile.into());
+        while let Some((x, y, tile)) = next_tile(&mut cpu, || 0)? {
+            tiles.insert((x, y), Tile::try_from(tile)?);
         }
 
-        let _ = handle.join();
-
-        tiles.values().filter(|&t| t.eq(&Tile::Block)).count()
+        Ok(tiles.values().filter(|&t| t.eq(&Tile::Block)).count())
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let (io, tx, rx) = AsyncIO::new();
-        let mut cpu = IntcodeComputer::new(&mut input.clone(), io);
-        cpu.write(0, 2);
-
-        let handle = thread::spawn(move || { cpu.run() });
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        let mut cpu = IntcodeComputer::new(input, NoIO);
+        cpu.write(0, 2)
+            .map_err(|e| SolverError::new(format_args!("unable to insert quarters: {}", e)))?;
 
         let mut paddle_x = 0;
+        let mut ball_x = 0;
         let mut score = 0;
 
-        while let Ok((x, _y, tile)) = recv_tile(&rx) {
+        while let Some((x, _y, tile)) = next_tile(&mut cpu, || {
+            match paddle_x.cmp(&ball_x) {
+                Ordering::Less => 1,
+                Ordering::Equal => 0,
+                Ordering::Greater => -1,
+            }
+        })? {
             if x == -1 {
                 score = tile;
                 continue;
             }
 
-            let tile: Tile = tile.into();
+            let tile = Tile::try_from(tile)?;
 
             if tile == Tile::Paddle {
                 paddle_x = x;
             }
             else if tile == Tile::Ball {
-                let dir = match paddle_x.cmp(&x) {
-                    Ordering::Less => 1,
-                    Ordering::Equal => 0,
-                    Ordering::Greater => -1,
-                };
-
-                tx.send(dir).unwrap();
+                ball_x = x;
             }
         }
 
-        let _ = handle.join();
-
-        score
+        Ok(score)
     }
 }
 
-fn recv_tile(rx: &Receiver<i64>) -> Result<(i64, i64, i64), Box<dyn Error>> {
-    Ok((rx.recv()?, rx.recv()?, rx.recv()?))
+fn next_tile<F>(cpu: &mut IntcodeComputer<NoIO>, joystick: F) -> Result<Option<(i64, i64, i64)>, SolverError>
+    where F: Fn() -> i64 {
+    let mut output = vec![];
+
+    while output.len() < 3 {
+        match cpu.run_until_event()? {
+            Event::NeedInput => cpu.provide_input(joystick()),
+            Event::Output(val) => output.push(val),
+            Event::Halted => return Ok(None),
+        }
+    }
+
+    Ok(Some((output[0], output[1], output[2])))
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
-use crate::solver::Solver;
-use std::io::{self, BufRead, BufReader};
+use crate::solver::{read_lines, Solver, SolverError};
+use std::io;
 use regex::Regex;
 use std::collections::{HashMap, VecDeque};
 
@@ -10,21 +10,23 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: io::Read>(&self, r: R) -> Self::Input {
-        BufReader::new(r)
-            .lines()
-            .flatten()
-            .map(|s| Reaction::parse(&s))
+    fn parse_input<R: io::Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        read_lines(r)?
+            .into_iter()
+            .filter(|(_, s)| !s.trim().is_empty())
+            .map(|(n, s)| Reaction::parse(&s).map_err(|e| SolverError::at_line(n, &s, e)))
             .collect()
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
         let mut refinery = Refinery::new(input.clone());
-        refinery.make_fuel(1);
-        refinery.ore_amount
+        if !refinery.make_fuel(1) {
+            return Err(SolverError::new("no reactions lead from ORE to FUEL"));
+        }
+        Ok(refinery.ore_amount)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
         let mut refinery = Refinery::new(input.clone());
         let mut fuel_amount = 0;
         let estimate = 1_850_000;
@@ -35,7 +37,7 @@ impl Solver for Problem {
             fuel_amount += 1;
         }
 
-        fuel_amount
+        Ok(fuel_amount)
     }
 }
 
@@ -46,20 +48,24 @@ pub struct Reaction {
 }
 
 impl Reaction {
-    pub fn parse(s: &str) -> (String, Self) {
-        let regex = Regex::new(r"(\d+) (\w+)").unwrap();
-        let elements = regex.captures_iter(s)
-            .map(|c| (c[2].to_string(), c[1].parse().unwrap()))
-            .collect::<Vec<_>>();
+    pub fn parse(s: &str) -> Result<(String, Self), String> {
+        let regex = Regex::new(r"^\s*(\d+) (\w+)\s*$").unwrap();
+        let element = |s: &str| -> Result<(String, u64), String> {
+            let c = regex.captures(s).ok_or_else(|| format!("invalid element {:?}", s.trim()))?;
+            let amount = c[1].parse().map_err(|_| format!("invalid amount {}", &c[1]))?;
+            Ok((c[2].to_string(), amount))
+        };
 
-        let ((elem, amount), input) = elements.split_last().unwrap();
+        let (input, output) = s.split_once("=>").ok_or("missing =>")?;
+        let input = input.split(',').map(element).collect::<Result<Vec<_>, _>>()?;
+        let (elem, amount) = element(output)?;
 
         let reaction = Reaction {
-            output_amount: *amount,
-            input: input.to_vec(),
+            output_amount: amount,
+            input,
         };
 
-        (elem.clone(), reaction)
+        Ok((elem, reaction))
     }
 }
 
@@ -1,11 +1,8 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, AsyncIO, read_program};
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::{IntcodeComputer, Event, NoIO};
 use std::{
     io::Read,
-    collections::HashSet,
-    iter::repeat,
-    thread,
-    time::Duration
+    collections::{HashSet, VecDeque},
 };
 
 pub struct Problem;
@@ -15,89 +12,71 @@ impl Solver for Problem {
     type Output1 = u64;
     type Output2 = u64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        let mut program = read_program(r);
-
-        // intcode setup
-        let (io, tx, rx) = AsyncIO::new();
-        let mut cpu = IntcodeComputer::new(&mut program, io);
-
-        // start intcode computer
-        let handle = thread::spawn(move || cpu.run());
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        let program = parse_program(r)?;
 
         let mut grid = Grid::new();
-
-        let mut pos = (0, 0);
-        grid.set_visited(pos);
-        let mut path = vec![];
-
-        let mut dir = grid.get_possible_direction(pos).unwrap();
-
-        tx.send(dir.into()).unwrap();
-
-        while let Ok(reply) = rx.recv() {
-            match reply {
-                0 => grid.hit_wall(pos, dir),
-                1 => {
-                    pos = dir.move_from_pos(pos);
-                    if !grid.visited(&pos) {
-                        path.push(dir);
-                    }
-                    grid.set_visited(pos);
-                },
-                2 => {
-                    pos = dir.move_from_pos(pos);
-                    if !grid.visited(&pos) {
-                        path.push(dir);
-                    }
-                    grid.set_visited(pos);
-                    grid.oxygen_system = Some(pos);
-                    grid.path_length = path.len() as u64;
-                },
-                _ => panic!("Invalid reply: {}", reply),
-            }
-
-            if let Some(next_dir) = grid.get_possible_direction(pos) {
-                dir = next_dir;
-                tx.send(dir.into()).unwrap();
-            }
-            else if let Some(next_dir) = path.pop() {
-                dir = next_dir.get_opposite();
-                tx.send(dir.into()).unwrap();
+        grid.set_visited((0, 0));
+
+        // explore breadth first, forking the droid at every open cell
+        let mut queue = VecDeque::new();
+        queue.push_back(((0, 0), 0, IntcodeComputer::new(&program, NoIO)));
+
+        while let Some((pos, path_length, cpu)) = queue.pop_front() {
+            while let Some(dir) = grid.get_possible_direction(pos) {
+                let mut droid = cpu.clone();
+                let new_pos = dir.move_from_pos(pos);
+
+                match move_droid(&mut droid, dir)? {
+                    0 => grid.hit_wall(pos, dir),
+                    1 => {
+                        grid.set_visited(new_pos);
+                        queue.push_back((new_pos, path_length + 1, droid));
+                    },
+                    2 => {
+                        grid.set_visited(new_pos);
+                        grid.oxygen_system = Some(new_pos);
+                        grid.path_length = path_length + 1;
+                        queue.push_back((new_pos, path_length + 1, droid));
+                    },
+                    reply => return Err(SolverError::new(format_args!("invalid reply from the droid: {}", reply))),
+                }
             }
-            else {
-                break;
-            }
-
-            grid.display(Some(pos));
-            println!("Path length: {}", path.len());
-            thread::sleep(Duration::from_millis(40));
         }
 
-        drop(tx);
-        let _ = handle.join();
+        let oxygen_system = grid.oxygen_system
+            .ok_or_else(|| SolverError::new("the droid never found the oxygen system"))?;
+        grid.oxygen.insert(oxygen_system);
 
-        grid.oxygen.insert(grid.oxygen_system.unwrap());
-
-        grid
+        Ok(grid)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        input.path_length
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        Ok(input.path_length)
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
         let mut minutes = 0;
         let mut grid = input.clone();
 
         while grid.spread_oxygen() {
             minutes += 1;
-            grid.display(None);
-            println!("Minutes: {}", minutes);
-            thread::sleep(Duration::from_millis(40));
         }
 
-        minutes
+        Ok(minutes)
+    }
+
+    fn draw(&self, input: &Self::Input) -> Result<Option<String>, SolverError> {
+        Ok(Some(input.render()))
+    }
+}
+
+fn move_droid(cpu: &mut IntcodeComputer<NoIO>, dir: Direction) -> Result<i64, SolverError> {
+    cpu.provide_input(dir.into());
+
+    match cpu.run_until_event()? {
+        Event::Output(reply) => Ok(reply),
+        event => Err(SolverError::new(format_args!("unexpected event from the droid: {:?}", event))),
     }
 }
 
@@ -118,20 +97,11 @@ impl Direction {
             Direction::East  => (x + 1, y),
         }
     }
-
-    fn get_opposite(&self) -> Self {
-        match self {
-            Direction::North => Direction::South,
-            Direction::South => Direction::North,
-            Direction::West  => Direction::East,
-            Direction::East  => Direction::West,
-        }
-    }
 }
 
-impl Into<i64> for Direction {
-    fn into(self) -> i64 {
-        match self {
+impl From<Direction> for i64 {
+    fn from(dir: Direction) -> Self {
+        match dir {
             Direction::North => 1,
             Direction::South => 2,
             Direction::West  => 3,
@@ -162,7 +132,7 @@ impl Grid {
     }
 
     fn get_possible_direction(&self, pos: (isize, isize)) -> Option<Direction> {
-        let dirs = vec![Direction::North, Direction::South, Direction::West, Direction::East];
+        let dirs = [Direction::North, Direction::South, Direction::West, Direction::East];
         dirs
             .iter()
             .filter(|&d| {
@@ -181,10 +151,6 @@ impl Grid {
          self.visited.insert(pos);
     }
 
-    fn visited(&self, pos: &(isize, isize)) -> bool {
-        self.visited.contains(pos)
-    }
-
     fn spread_oxygen(&mut self) -> bool {
         let mut spread = false;
         let mut new_positions = vec![];
@@ -193,7 +159,7 @@ impl Grid {
             new_positions.append(&mut self.spread_from_pos(pos));
         }
 
-        if new_positions.len() > 0 {
+        if !new_positions.is_empty() {
             spread = true;
         }
 
@@ -205,7 +171,7 @@ impl Grid {
     }
 
     fn spread_from_pos(&self, pos: (isize, isize)) -> Vec<(isize, isize)> {
-        let dirs = vec![Direction::North, Direction::South, Direction::West, Direction::East];
+        let dirs = [Direction::North, Direction::South, Direction::West, Direction::East];
         dirs
             .iter()
             .map(|d| d.move_from_pos(pos))
@@ -213,8 +179,7 @@ impl Grid {
             .collect()
     }
 
-    fn display(&self, pos: Option<(isize, isize)>) {
-        print!("\x1B[2J");
+    fn render(&self) -> String {
         let mut min_x = 0;
         let mut max_x = 0;
         let mut min_y = 0;
@@ -227,20 +192,12 @@ impl Grid {
             else if y > max_y { max_y = y; }
         }
 
-        if let Some((x, y)) = pos {
-            if x < min_x { min_x = x; }
-            else if x > max_x { max_x = x; }
-            if y < min_y { min_y = y; }
-            else if y > max_y { max_y = y; }
-        }
-
         let xoff = -min_x;
         let yoff = -min_y;
         let width = max_x - min_x + 1;
         let height = max_y - min_y + 1;
 
-        let mut chars: Vec<Vec<char>> = repeat(repeat(' ').take(width as usize).collect())
-            .take(height as usize).collect();
+        let mut chars: Vec<Vec<char>> = vec![vec![' '; width as usize]; height as usize];
 
         for &(x, y) in self.walls.iter() {
             chars[(height - (y + yoff) - 1) as usize][(x + xoff) as usize] = '█';
@@ -250,19 +207,17 @@ impl Grid {
             chars[(height - (y + yoff) - 1) as usize][(x + xoff) as usize] = '░';
         }
 
-        if let Some((x, y)) = pos {
-            chars[(height - (y + yoff) - 1) as usize][(x + xoff) as usize] = '@';
-        }
+        chars[(height - yoff - 1) as usize][xoff as usize] = '@';
 
         if let Some((x, y)) = self.oxygen_system {
             chars[(height - (y + yoff) - 1) as usize][(x + xoff) as usize] = 'X';
         }
 
+        let mut picture = String::new();
         for row in chars {
-            for c in row {
-                print!("{}", c);
-            }
-            println!();
+            picture.extend(row);
+            picture.push('\n');
         }
+        picture
     }
 }
\ No newline at end of file
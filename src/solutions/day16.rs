@@ -1,5 +1,5 @@
-use crate::solver::Solver;
-use std::io::{Read, BufReader};
+use crate::solver::{read_lines, Solver, SolverError};
+use std::io::Read;
 use itertools::Itertools;
 use rayon::prelude::*;
 
@@ -11,34 +11,51 @@ impl Solver for Problem {
     type Output1 = String;
     type Output2 = String;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        BufReader::new(r)
-            .bytes()
-            .flatten()
-            .map(|b| b - b'0')
-            .collect()
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        let mut signal = vec![];
+        for (n, line) in read_lines(r)? {
+            for c in line.trim().chars() {
+                let digit = c.to_digit(10)
+                    .ok_or_else(|| SolverError::at_line(n, &line, format_args!("invalid digit {:?}", c)))?;
+                signal.push(digit as u8);
+            }
+        }
+
+        if signal.is_empty() {
+            return Err(SolverError::new("empty signal"));
+        }
+        Ok(signal)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
         let mut list = input.clone();
         for _ in 0..100 {
             list = fft(list);
         }
 
-        list.iter().take(8).join("")
+        Ok(list.iter().take(8).join(""))
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        let offset = input.iter()
-            .take(7)
-            .join("")
-            .parse()
-            .unwrap_or(0);
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        if input.len() < 7 {
+            return Err(SolverError::new("signal too short to hold the message offset"));
+        }
+        let offset = input.iter().take(7).fold(0, |n, &d| n * 10 + d as usize);
+
+        // every digit past the middle is the sum of the digits after it,
+        // which only holds in the second half of the signal
+        let len = 10000 * input.len();
+        if offset < len / 2 || offset + 8 > len {
+            return Err(SolverError::new(format_args!(
+                "message offset {} is outside the second half of the {} digit signal",
+                offset,
+                len,
+            )));
+        }
 
-        let len = input.len();
         let mut list = input.iter()
             .cycle()
-            .take(10000 * len)
+            .take(len)
             .skip(offset)
             .cloned()
             .collect::<Vec<_>>();
@@ -49,7 +66,7 @@ impl Solver for Problem {
             }
         }
 
-        list.iter().take(8).join("")
+        Ok(list.iter().take(8).join(""))
     }
 }
 
@@ -58,8 +75,8 @@ fn fft(input: Vec<u8>) -> Vec<u8> {
         .into_par_iter()
         .map(|i| {
             let mut n = 0;
-            for j in i..input.len() {
-                n += get_pattern(i, j) * input[j] as isize;
+            for (j, &v) in input.iter().enumerate().skip(i) {
+                n += get_pattern(i, j) * v as isize;
             }
             (n.abs() % 10) as u8
         })
@@ -68,5 +85,5 @@ fn fft(input: Vec<u8>) -> Vec<u8> {
 
 fn get_pattern(i: usize, j: usize) -> isize {
     let base = [0, 1, 0, -1];
-    base[(((j + 1) / (i + 1)) % 4) as usize]
+    base[((j + 1) / (i + 1)) % 4]
 }
\ No newline at end of file
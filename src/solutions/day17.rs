@@ -1,9 +1,7 @@
-use crate::solver::Solver;
-use crate::intcode_computer::{IntcodeComputer, AsyncIO, read_program};
+use crate::solver::{parse_program, Solver, SolverError};
+use crate::intcode_computer::{IntcodeComputer, AsciiIO};
 use std::{
     io::Read,
-    thread,
-    iter::from_fn,
     str::FromStr,
     convert::TryFrom,
     fmt::{Display, Formatter, Error}
@@ -12,48 +10,111 @@ use itertools::Itertools;
 
 pub struct Problem;
 
+const MAX_ROUTINE_LEN: usize = 20;
+const FUNCTIONS: [&str; 3] = ["A", "B", "C"];
+
+fn camera_view(program: &[i64]) -> Result<Grid, SolverError> {
+    let mut cpu = IntcodeComputer::new(program, AsciiIO::new());
+    cpu.run()?;
+
+    Grid::from_str(cpu.io.text()).map_err(|_| SolverError::new("unreadable camera view"))
+}
+
 
 impl Solver for Problem {
     type Input = Vec<i64>;
     type Output1 = usize;
     type Output2 = i64;
 
-    fn parse_input<R: Read>(&self, r: R) -> Self::Input {
-        read_program(r)
+    fn parse_input<R: Read>(&self, r: R) -> Result<Self::Input, SolverError> {
+        parse_program(r)
     }
 
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1 {
-        // intcode setup
-        let (io, tx, rx) = AsyncIO::new();
-        let mut cpu = IntcodeComputer::new(&mut input.clone(), io);
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+        let grid = camera_view(input)?;
 
-        let handle = thread::spawn(move || cpu.run());
+        Ok((1..grid.width.saturating_sub(1))
+            .cartesian_product(1..grid.height.saturating_sub(1))
+            .filter(|&pos| grid.is_intersecion(pos))
+            .map(|(x, y)| x * y)
+            .sum())
+    }
 
-        let s = String::from_utf8(
-            from_fn(|| rx.recv().ok())
-                .map(|v| v as u8)
-                .collect::<Vec<_>>()
-        )
-        .unwrap();
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+        let path = camera_view(input)?.path()
+            .ok_or_else(|| SolverError::new("no robot on the map"))?;
 
-        drop(tx);
-        let _ = handle.join();
+        let mut functions = vec![];
+        let mut main = vec![];
+        if !compress(&path, &mut functions, &mut main) {
+            return Err(SolverError::new("path does not fit into three functions"));
+        }
 
-        let grid = Grid::from_str(&s).unwrap();
+        let mut io = AsciiIO::new();
+        io.push_line(&main.iter().map(|&i| FUNCTIONS[i]).join(","));
+        for function in &functions {
+            io.push_line(&routine(function));
+        }
+        io.push_line("n");
 
-        println!("{}", grid);
+        let mut cpu = IntcodeComputer::new(input, io);
+        cpu.write(0, 2)
+            .map_err(|e| SolverError::new(format_args!("unable to wake up the robot: {}", e)))?;
+        cpu.run()?;
 
-        (1..grid.width - 1)
-            .into_iter()
-            .cartesian_product((1..grid.height - 1).into_iter())
-            .filter(|&pos| grid.is_intersecion(pos))
-            .map(|(x, y)| x * y)
-            .sum()
+        cpu.io.results().last().copied().ok_or_else(|| SolverError::new("robot reported no dust"))
+    }
+
+    fn draw(&self, input: &Self::Input) -> Result<Option<String>, SolverError> {
+        Ok(Some(camera_view(input)?.to_string()))
+    }
+}
+
+type Move = (char, usize);
+
+fn routine(moves: &[Move]) -> String {
+    moves.iter().map(|(turn, steps)| format!("{},{}", turn, steps)).join(",")
+}
+
+/// Splits `path` into at most three functions, returning the order they are
+/// called in through `main`.
+fn compress<'a>(path: &'a [Move], functions: &mut Vec<&'a [Move]>, main: &mut Vec<usize>) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+    if 2 * main.len() + 1 > MAX_ROUTINE_LEN {
+        return false;
+    }
+
+    for i in 0..functions.len() {
+        let function = functions[i];
+        if path.starts_with(function) {
+            main.push(i);
+            if compress(&path[function.len()..], functions, main) {
+                return true;
+            }
+            main.pop();
+        }
     }
 
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2 {
-        1
+    if functions.len() < FUNCTIONS.len() {
+        for len in 1..=path.len() {
+            let function = &path[..len];
+            if routine(function).len() > MAX_ROUTINE_LEN {
+                break;
+            }
+
+            functions.push(function);
+            main.push(functions.len() - 1);
+            if compress(&path[len..], functions, main) {
+                return true;
+            }
+            main.pop();
+            functions.pop();
+        }
     }
+
+    false
 }
 
 #[derive(Eq, PartialEq)]
@@ -63,7 +124,7 @@ enum Cell {
     Bot(Direction),
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 enum Direction {
     Up,
     Down,
@@ -71,6 +132,30 @@ enum Direction {
     Right
 }
 
+impl Direction {
+    fn delta(self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+        }
+    }
+
+    fn left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Left => Direction::Down,
+            Direction::Down => Direction::Right,
+            Direction::Right => Direction::Up,
+        }
+    }
+
+    fn right(self) -> Direction {
+        self.left().left().left()
+    }
+}
+
 impl TryFrom<u8> for Cell {
     type Error = ();
 
@@ -82,7 +167,7 @@ impl TryFrom<u8> for Cell {
             b'v' => Ok(Cell::Bot(Direction::Down)),
             b'<' => Ok(Cell::Bot(Direction::Left)),
             b'>' => Ok(Cell::Bot(Direction::Right)),
-            c => Err(()),
+            _ => Err(()),
         }
     }
 }
@@ -115,6 +200,45 @@ impl Grid {
             Some(&Cell::Wall),
         )
     }
+
+    fn is_scaffold(&self, (x, y): (i64, i64)) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width
+            && self.get((x as usize, y as usize)) == Some(&Cell::Wall)
+    }
+
+    /// Follows the scaffold from the robot, going straight as long as
+    /// possible and turning when it has to.
+    fn path(&self) -> Option<Vec<Move>> {
+        let (i, mut dir) = self.cells.iter()
+            .enumerate()
+            .find_map(|(i, cell)| match cell {
+                Cell::Bot(dir) => Some((i, *dir)),
+                _ => None,
+            })?;
+        let mut pos = ((i % self.width) as i64, (i / self.width) as i64);
+        let step = |(x, y): (i64, i64), dir: Direction| (x + dir.delta().0, y + dir.delta().1);
+
+        let mut moves = vec![];
+        loop {
+            let (turn, next) = if self.is_scaffold(step(pos, dir.left())) {
+                ('L', dir.left())
+            }
+            else if self.is_scaffold(step(pos, dir.right())) {
+                ('R', dir.right())
+            }
+            else {
+                return Some(moves);
+            };
+
+            dir = next;
+            let mut steps = 0;
+            while self.is_scaffold(step(pos, dir)) {
+                pos = step(pos, dir);
+                steps += 1;
+            }
+            moves.push((turn, steps));
+        }
+    }
 }
 
 impl FromStr for Grid {
@@ -127,7 +251,7 @@ impl FromStr for Grid {
             .collect::<Result<Vec<_>, _>>()?;
 
         let height = cells.len();
-        let width = cells.first().unwrap().len();
+        let width = cells.first().ok_or(())?.len();
 
         Ok(Grid {
             width,
@@ -1,5 +1,5 @@
 // GENERATED ON BUILD, DO NOT EDIT
-use crate::solver::Solver;
+use crate::solver::{DayResult, Options, Solver, SolverError};
 
 mod day01;
 mod day02;
@@ -19,25 +19,27 @@ mod day15;
 mod day16;
 mod day17;
 
-pub fn exec_day(day: i32) {
+pub const DAYS: &[i32] = &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17];
+
+pub fn exec_day(day: i32, options: &Options) -> DayResult {
     match day {
-        1 => day01::Problem {}.solve(day),
-        2 => day02::Problem {}.solve(day),
-        3 => day03::Problem {}.solve(day),
-        4 => day04::Problem {}.solve(day),
-        5 => day05::Problem {}.solve(day),
-        6 => day06::Problem {}.solve(day),
-        7 => day07::Problem {}.solve(day),
-        8 => day08::Problem {}.solve(day),
-        9 => day09::Problem {}.solve(day),
-        10 => day10::Problem {}.solve(day),
-        11 => day11::Problem {}.solve(day),
-        12 => day12::Problem {}.solve(day),
-        13 => day13::Problem {}.solve(day),
-        14 => day14::Problem {}.solve(day),
-        15 => day15::Problem {}.solve(day),
-        16 => day16::Problem {}.solve(day),
-        17 => day17::Problem {}.solve(day),
-        d => println!("Day {} hasn't been solved yet :(", d),
+        1 => day01::Problem {}.solve(day, options),
+        2 => day02::Problem {}.solve(day, options),
+        3 => day03::Problem {}.solve(day, options),
+        4 => day04::Problem {}.solve(day, options),
+        5 => day05::Problem {}.solve(day, options),
+        6 => day06::Problem {}.solve(day, options),
+        7 => day07::Problem {}.solve(day, options),
+        8 => day08::Problem {}.solve(day, options),
+        9 => day09::Problem {}.solve(day, options),
+        10 => day10::Problem {}.solve(day, options),
+        11 => day11::Problem {}.solve(day, options),
+        12 => day12::Problem {}.solve(day, options),
+        13 => day13::Problem {}.solve(day, options),
+        14 => day14::Problem {}.solve(day, options),
+        15 => day15::Problem {}.solve(day, options),
+        16 => day16::Problem {}.solve(day, options),
+        17 => day17::Problem {}.solve(day, options),
+        d => DayResult::failed(d, SolverError::new("hasn't been solved yet :(")),
     }
 }
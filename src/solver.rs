@@ -1,38 +1,551 @@
 use std::{
-    fmt::Display,
-    fs::File,
-    io::{self},
-    path::Path,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fmt::Write,
+    fs,
+    io::{self, BufRead, BufReader, Cursor, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use aoc_2019::intcode_computer::{
+    network::{NodeError, TopologyError},
+    IntcodeError,
 };
 
 fn input_file(day: i32) -> String {
     format!("input/day{:02}", day)
 }
 
+fn answers_file(dir: &Path, day: i32) -> PathBuf {
+    dir.join(format!("day{:02}", day))
+}
+
+/// Expected answers of a day, the answer to part 1 on the first line and
+/// to part 2 on the second.
+fn read_answers<P: AsRef<Path>>(path: P) -> Result<Vec<String>, SolverError> {
+    let answers = fs::read_to_string(&path).map_err(|e| {
+        SolverError::new(format_args!("no expected answers in {}: {}", path.as_ref().display(), e))
+    })?;
+
+    Ok(answers.lines().map(|l| l.trim().to_string()).collect())
+}
+
+/// Reads the file at `p`, or stdin if `p` is `-`.
+fn read_input<P: AsRef<Path>>(p: P) -> Result<Vec<u8>, SolverError> {
+    if p.as_ref() == Path::new("-") {
+        let mut buf = vec![];
+        io::stdin().read_to_end(&mut buf)?;
+        return Ok(buf);
+    }
+
+    fs::read(&p).map_err(|e| {
+        SolverError::new(format_args!("unable to open {}: {}", p.as_ref().display(), e))
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("invalid format {:?}, expected text, json or csv", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    /// Solve only this part.
+    pub part: Option<u8>,
+    /// Read the input from this file instead of `input/dayNN`, or from
+    /// stdin if it is `-`.
+    pub input: Option<PathBuf>,
+    /// Print the answers and nothing else.
+    pub quiet: bool,
+    /// Time this many runs of every phase instead of a single one.
+    pub bench: Option<usize>,
+    /// Compare the answers with the ones in `answers/dayNN`.
+    pub verify: bool,
+    /// Look for the expected answers in this directory instead of `answers`.
+    pub answers: Option<PathBuf>,
+    /// How results are reported. Anything but `Text` prints nothing while
+    /// solving and leaves the output to the caller.
+    pub format: Format,
+}
+
+/// Wall time statistics of one phase over all of its timed runs.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+}
+
+impl Stats {
+    fn new(mut samples: Vec<Duration>) -> Self {
+        samples.sort();
+        let runs = samples.len();
+        let median = if runs.is_multiple_of(2) {
+            (samples[runs / 2 - 1] + samples[runs / 2]) / 2
+        }
+        else {
+            samples[runs / 2]
+        };
+
+        Self {
+            runs,
+            min: samples[0],
+            median,
+            mean: samples.iter().sum::<Duration>() / runs as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Timings {
+    pub parse: Stats,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+}
+
+impl Timings {
+    /// Sum of the median times of every phase that ran.
+    pub fn total(&self) -> Duration {
+        self.parse.median
+            + self.part1.map_or(Duration::ZERO, |s| s.median)
+            + self.part2.map_or(Duration::ZERO, |s| s.median)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PartResult {
+    pub part: u8,
+    pub answer: String,
+    pub time: Stats,
+    /// Whether the answer matched the expected one, when verifying.
+    pub passed: Option<bool>,
+}
+
+/// Everything known about a day after trying to solve it. `parse` and
+/// `parts` hold whatever completed before an error.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DayResult {
+    pub day: i32,
+    pub parse: Option<Stats>,
+    pub parts: Vec<PartResult>,
+    pub error: Option<SolverError>,
+}
+
+impl DayResult {
+    pub fn new(day: i32) -> Self {
+        Self {
+            day,
+            parse: None,
+            parts: vec![],
+            error: None,
+        }
+    }
+
+    pub fn failed(day: i32, e: SolverError) -> Self {
+        Self {
+            error: Some(SolverError { day: Some(day), ..e }),
+            ..Self::new(day)
+        }
+    }
+
+    pub fn timings(&self) -> Option<Timings> {
+        let time = |part| self.parts.iter().find(|p| p.part == part).map(|p| p.time);
+        self.parse.map(|parse| Timings { parse, part1: time(1), part2: time(2) })
+    }
+}
+
+/// Runs `f` once, or with `bench` set, once more for each of `bench` timed
+/// runs, the first run then serving as the warmup. Returns the result of
+/// the first run.
+fn measure<T, F>(bench: Option<usize>, mut f: F) -> Result<(T, Stats), SolverError>
+    where F: FnMut() -> Result<T, SolverError> {
+    let start = Instant::now();
+    let result = f()?;
+    let mut samples = vec![start.elapsed()];
+
+    if let Some(runs) = bench {
+        samples.clear();
+
+        for _ in 0..runs.max(1) {
+            let start = Instant::now();
+            f()?;
+            samples.push(start.elapsed());
+        }
+    }
+
+    Ok((result, Stats::new(samples)))
+}
+
+pub fn format_duration(d: Duration) -> String {
+    if d < Duration::from_millis(1) {
+        format!("{:.1}µs", d.as_secs_f64() * 1e6)
+    }
+    else if d < Duration::from_secs(1) {
+        format!("{:.2}ms", d.as_secs_f64() * 1e3)
+    }
+    else {
+        format!("{:.2}s", d.as_secs_f64())
+    }
+}
+
+fn print_timings(timings: &Timings, bench: bool) {
+    let phases = [("parse", Some(timings.parse)), ("part 1", timings.part1), ("part 2", timings.part2)];
+    let phases = phases.iter().filter_map(|&(name, stats)| stats.map(|s| (name, s)));
+
+    if bench {
+        for (name, s) in phases {
+            println!(
+                "{:<8}min {:>9}  median {:>9}  mean {:>9}  ({} runs)",
+                name,
+                format_duration(s.min),
+                format_duration(s.median),
+                format_duration(s.mean),
+                s.runs,
+            );
+        }
+    }
+    else {
+        let times = phases.map(|(name, s)| format!("{} {}", name, format_duration(s.median))).collect::<Vec<_>>();
+        println!("Time: {}", times.join(", "));
+    }
+}
+
+/// One row per day with the median time of each phase, plus the total.
+pub fn summary_table(days: &[(i32, Timings)]) -> String {
+    let phase = |stats: Option<Stats>| stats.map_or("-".to_string(), |s| format_duration(s.median));
+    let mut table = String::new();
+
+    writeln!(table, "{:>4}{:>12}{:>12}{:>12}{:>12}", "day", "parse", "part 1", "part 2", "total").unwrap();
+    for (day, t) in days {
+        writeln!(
+            table,
+            "{:>4}{:>12}{:>12}{:>12}{:>12}",
+            day,
+            phase(Some(t.parse)),
+            phase(t.part1),
+            phase(t.part2),
+            format_duration(t.total()),
+        ).unwrap();
+    }
+
+    let total = days.iter().map(|(_, t)| t.total()).sum::<Duration>();
+    writeln!(table, "{:>4}{:>48}", "all", format_duration(total)).unwrap();
+    table
+}
+
+/// Why a day couldn't be solved. `line` (counted from 1) and `text` point
+/// at the offending part of the input when there is one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SolverError {
+    pub day: Option<i32>,
+    pub line: Option<usize>,
+    pub text: Option<String>,
+    pub reason: String,
+}
+
+impl SolverError {
+    pub fn new<S: Display>(reason: S) -> Self {
+        Self {
+            day: None,
+            line: None,
+            text: None,
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn at_line<S: Display>(line: usize, text: &str, reason: S) -> Self {
+        Self {
+            line: Some(line),
+            text: Some(text.to_string()),
+            ..Self::new(reason)
+        }
+    }
+}
+
+impl Display for SolverError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {}: ", day)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(f, "{}", self.reason)?;
+        if let Some(text) = &self.text {
+            write!(f, " in {:?}", text)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for SolverError {}
+
+impl From<io::Error> for SolverError {
+    fn from(e: io::Error) -> Self {
+        Self::new(e)
+    }
+}
+
+impl From<IntcodeError> for SolverError {
+    fn from(e: IntcodeError) -> Self {
+        Self::new(format_args!("Intcode program failed: {}", e))
+    }
+}
+
+impl From<NodeError> for SolverError {
+    fn from(e: NodeError) -> Self {
+        Self::new(format_args!("Intcode program failed: {}", e))
+    }
+}
+
+impl From<TopologyError> for SolverError {
+    fn from(e: TopologyError) -> Self {
+        Self::new(format_args!("invalid Intcode network: {}", e))
+    }
+}
+
+/// Reads all lines of the input, numbered from 1.
+pub fn read_lines<R: Read>(r: R) -> Result<Vec<(usize, String)>, SolverError> {
+    BufReader::new(r)
+        .lines()
+        .enumerate()
+        .map(|(i, line)| line.map(|line| (i + 1, line)).map_err(SolverError::from))
+        .collect()
+}
+
+/// Parses every non-empty line of the input.
+pub fn parse_lines<R: Read, T>(r: R) -> Result<Vec<T>, SolverError>
+    where T: FromStr, T::Err: Display {
+    read_lines(r)?
+        .into_iter()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| line.trim().parse().map_err(|e| SolverError::at_line(n, &line, e)))
+        .collect()
+}
+
+/// Parses a comma separated Intcode program.
+pub fn parse_program<R: Read>(r: R) -> Result<Vec<i64>, SolverError> {
+    let mut program = vec![];
+
+    for (n, line) in read_lines(r)? {
+        for value in line.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            let value = value.parse()
+                .map_err(|_| SolverError::at_line(n, value, "invalid Intcode value"))?;
+            program.push(value);
+        }
+    }
+
+    Ok(program)
+}
+
 pub trait Solver {
     type Input;
     type Output1: Display;
     type Output2: Display;
 
-    fn parse_input<R: io::Seek + io::Read>(&self, r: R) -> Self::Input;
-    fn solve_first(&self, input: &Self::Input) -> Self::Output1;
-    fn solve_second(&self, input: &Self::Input) -> Self::Output2;
+    fn parse_input<R: io::Seek + io::Read>(&self, r: R) -> Result<Self::Input, SolverError>;
+    fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError>;
+    fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError>;
 
-    fn load_input<P: AsRef<Path>>(&self, p: P) -> io::Result<Self::Input> {
-        let f = File::open(p)?;
-        Ok(self.parse_input(f))
+    /// A picture of the puzzle, shown after the answers in text format
+    /// unless only the answers were asked for.
+    fn draw(&self, _input: &Self::Input) -> Result<Option<String>, SolverError> {
+        Ok(None)
     }
 
-    fn solve(&self, day: i32) {
-        let input_file = input_file(day);
-        let input = self
-            .load_input(input_file)
-            .expect("unable to open input file");
+    /// Solves the parts selected by `options`. In text format the answers
+    /// are printed as they come, followed by the picture from `draw` and the
+    /// time each phase took. When verifying, answers that don't match the
+    /// expected ones make the day fail after both parts ran.
+    fn solve(&self, day: i32, options: &Options) -> DayResult {
+        let text = options.format == Format::Text;
+        // pictures would only clutter benchmarks and verification
+        let draw = text && !options.quiet && options.bench.is_none() && !options.verify;
+
+        let mut result = DayResult::new(day);
+        let mut mismatches = vec![];
 
-        let s1 = self.solve_first(&input);
-        let s2 = self.solve_second(&input);
+        let outcome = (|| -> Result<(), SolverError> {
+            let expected = match &options.answers {
+                _ if !options.verify => None,
+                Some(dir) => Some(read_answers(answers_file(dir, day))?),
+                None => Some(read_answers(answers_file(Path::new("answers"), day))?),
+            };
 
-        println!("Solution 1: {}", s1);
-        println!("Solution 2: {}", s2);
+            let data = match &options.input {
+                Some(path) => read_input(path)?,
+                None => read_input(input_file(day))?,
+            };
+
+            let (input, parse) = measure(options.bench, || self.parse_input(Cursor::new(&data[..])))?;
+            result.parse = Some(parse);
+
+            let mut add = |part: u8, answer: &dyn Display, time: Stats| {
+                let answer = answer.to_string();
+                let passed = expected.as_ref().map(|expected| match expected.get(part as usize - 1) {
+                    Some(e) if *e == answer => true,
+                    Some(e) => {
+                        mismatches.push(format!("part {}: expected {}, got {}", part, e, answer));
+                        false
+                    }
+                    None => {
+                        mismatches.push(format!("part {}: no expected answer", part));
+                        false
+                    }
+                });
+
+                match (text, passed, options.quiet) {
+                    (false, _, _) => {}
+                    (true, _, true) => println!("{}", answer),
+                    (true, None, false) => println!("Solution {}: {}", part, answer),
+                    (true, Some(true), false) => println!("Solution {}: {} (pass)", part, answer),
+                    (true, Some(false), false) => println!("Solution {}: {} (FAIL)", part, answer),
+                }
+
+                result.parts.push(PartResult { part, answer, time, passed });
+            };
+
+            if options.part != Some(2) {
+                let (answer, time) = measure(options.bench, || self.solve_first(&input))?;
+                add(1, &answer, time);
+            }
+            if options.part != Some(1) {
+                let (answer, time) = measure(options.bench, || self.solve_second(&input))?;
+                add(2, &answer, time);
+            }
+
+            if draw {
+                if let Some(picture) = self.draw(&input)? {
+                    print!("{}", picture);
+                }
+            }
+            if text && !options.quiet {
+                print_timings(&result.timings().unwrap(), options.bench.is_some());
+            }
+            Ok(())
+        })();
+
+        let error = match outcome {
+            Err(e) => Some(e),
+            Ok(()) if !mismatches.is_empty() => Some(SolverError::new(mismatches.join(", "))),
+            Ok(()) => None,
+        };
+
+        result.error = error.map(|e| SolverError { day: Some(day), ..e });
+        result
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_lines::<_, i64>("12\n\n7\n".as_bytes()), Ok(vec![12, 7]));
+        assert_eq!(parse_program("1,2,\n3\n".as_bytes()), Ok(vec![1, 2, 3]));
+
+        let err = parse_program("1,2\n3,x4\n".as_bytes()).unwrap_err();
+        assert_eq!((err.line, err.text.as_deref()), (Some(2), Some("x4")));
+
+        let err = SolverError { day: Some(1), ..parse_lines::<_, i64>("12\nabc".as_bytes()).unwrap_err() };
+        assert_eq!(err.to_string(), "day 1: line 2: invalid digit found in string in \"abc\"");
+    }
+
+    #[test]
+    fn test_stats() {
+        let ms = Duration::from_millis;
+        let stats = Stats::new(vec![ms(9), ms(1), ms(4), ms(2)]);
+        assert_eq!(stats, Stats { runs: 4, min: ms(1), median: ms(3), mean: ms(4) });
+
+        let mut runs = 0;
+        let (first, stats) = measure(Some(3), || { runs += 1; Ok(runs) }).unwrap();
+        assert_eq!((first, runs, stats.runs), (1, 4, 3));
+    }
+
+    struct Echo;
+
+    impl Solver for Echo {
+        type Input = String;
+        type Output1 = String;
+        type Output2 = usize;
+
+        fn parse_input<R: Read>(&self, mut r: R) -> Result<Self::Input, SolverError> {
+            let mut input = String::new();
+            r.read_to_string(&mut input)?;
+            Ok(input.trim().to_string())
+        }
+
+        fn solve_first(&self, input: &Self::Input) -> Result<Self::Output1, SolverError> {
+            Ok(input.clone())
+        }
+
+        fn solve_second(&self, input: &Self::Input) -> Result<Self::Output2, SolverError> {
+            Ok(input.len())
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let dir = std::env::temp_dir().join(format!("aoc-2019-verify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("input"), "abc\n").unwrap();
+
+        let verify = |answers: Option<&str>| {
+            let path = dir.join("day99");
+            match answers {
+                Some(answers) => fs::write(&path, answers).unwrap(),
+                None => { let _ = fs::remove_file(&path); }
+            }
+
+            let options = Options {
+                input: Some(dir.join("input")),
+                answers: Some(dir.clone()),
+                verify: true,
+                format: Format::Json,
+                ..Options::default()
+            };
+            let result = Echo.solve(99, &options);
+            let passed = result.parts.iter().map(|p| p.passed).collect::<Vec<_>>();
+            (passed, result.error.map(|e| e.to_string()))
+        };
+
+        assert_eq!(verify(Some("abc\n3\n")), (vec![Some(true), Some(true)], None));
+        assert_eq!(
+            verify(Some("abc\n4\n")),
+            (vec![Some(true), Some(false)], Some("day 99: part 2: expected 4, got 3".to_string()))
+        );
+        assert_eq!(
+            verify(Some("xyz\n")),
+            (
+                vec![Some(false), Some(false)],
+                Some("day 99: part 1: expected xyz, got abc, part 2: no expected answer".to_string())
+            )
+        );
+
+        let (passed, error) = verify(None);
+        assert!(passed.is_empty());
+        assert!(error.unwrap().starts_with("day 99: no expected answers in "));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}